@@ -1,59 +1,257 @@
-use std::fmt::Display;
+use std::{fmt::Display, rc::Rc};
 
-use crate::token::Token;
+use crate::{bigint::BigInt, token::Token};
+
+// Identifies a variable-referencing expression so the resolver can record
+// how many scopes away its binding lives.
+pub type ExprId = usize;
 
 #[derive(Clone)]
 pub enum LiteralKind {
     String(String),
-    Float(f32),
+    Integer(i64),
+    BigInteger(BigInt),
+    Float(f64),
     Bool(bool),
     Nil,
 }
 
 #[derive(Clone)]
 pub enum Expr {
+    Assign {
+        id: ExprId,
+        name: Token,
+        value: Box<Expr>,
+    },
+
     Binary {
         left: Box<Expr>,
         operator: Token,
         right: Box<Expr>,
     },
 
+    Call {
+        callee: Box<Expr>,
+        paren: Token,
+        arguments: Vec<Expr>,
+        // `name: value` arguments, which always come after the positional ones.
+        named: Vec<(Token, Expr)>,
+    },
+
+    Conditional {
+        condition: Box<Expr>,
+        then_branch: Box<Expr>,
+        else_branch: Box<Expr>,
+    },
+
+    Get {
+        object: Box<Expr>,
+        name: Token,
+    },
+
     Grouping {
         expr: Box<Expr>,
     },
 
+    Index {
+        object: Box<Expr>,
+        bracket: Token,
+        index: Box<Expr>,
+    },
+
+    IndexSet {
+        object: Box<Expr>,
+        bracket: Token,
+        index: Box<Expr>,
+        value: Box<Expr>,
+    },
+
+    // A string with embedded expressions; literal text and expressions alternate.
+    Interpolation {
+        parts: Vec<Expr>,
+    },
+
+    // An anonymous function, written `fun (x) { ... }` or `(x) => ...`.
+    Lambda {
+        function: Rc<Function>,
+    },
+
+    List {
+        elements: Vec<Expr>,
+    },
+
     Literal {
-        value: LiteralKind
+        value: LiteralKind,
+    },
+
+    Map {
+        brace: Token,
+        entries: Vec<(Expr, Expr)>,
+    },
+
+    Logical {
+        left: Box<Expr>,
+        operator: Token,
+        right: Box<Expr>,
+    },
+
+    Set {
+        object: Box<Expr>,
+        name: Token,
+        value: Box<Expr>,
+    },
+
+    Super {
+        id: ExprId,
+        keyword: Token,
+        method: Token,
+    },
+
+    This {
+        id: ExprId,
+        keyword: Token,
+    },
+
+    // A compound assignment or an increment/decrement: reads `target`, applies
+    // the binary `operator` to it and `value`, and stores the result back.
+    // The operator keeps the lexeme it was written with, such as "+=".
+    // Evaluates to the new value, or the old one if `postfix`.
+    Update {
+        target: Box<Expr>,
+        operator: Token,
+        value: Box<Expr>,
+        postfix: bool,
     },
 
     Unary {
         operator: Token,
         right: Box<Expr>,
     },
+
+    Variable {
+        id: ExprId,
+        name: Token,
+    },
+}
+
+#[derive(Clone)]
+pub enum Stmt {
+    Block {
+        statements: Vec<Stmt>,
+    },
+
+    Break {
+        keyword: Token,
+        label: Option<Token>,
+    },
+
+    Class {
+        name: Token,
+        superclass: Option<Expr>,
+        methods: Vec<Rc<Function>>,
+    },
+
+    Continue {
+        keyword: Token,
+        label: Option<Token>,
+    },
+
+    Expression {
+        expr: Expr,
+    },
+
+    Function {
+        function: Rc<Function>,
+    },
+
+    If {
+        condition: Expr,
+        then_branch: Box<Stmt>,
+        else_branch: Option<Box<Stmt>>,
+    },
+
+    Print {
+        expr: Expr,
+    },
+
+    Return {
+        keyword: Token,
+        value: Option<Expr>,
+    },
+
+    Throw {
+        keyword: Token,
+        value: Expr,
+    },
+
+    // At least one of `catch` and `finally` is present.
+    Try {
+        body: Vec<Stmt>,
+        catch: Option<Catch>,
+        finally: Option<Vec<Stmt>>,
+    },
+
+    Var {
+        name: Token,
+        initializer: Option<Expr>,
+    },
+
+    While {
+        condition: Expr,
+        body: Box<Stmt>,
+        // A desugared `for` loop's increment. It can't just be appended to
+        // the body as the rest of `for` is, because `continue` skips the rest
+        // of the body but must still run the increment; this field exists
+        // only for that.
+        increment: Option<Expr>,
+        label: Option<Token>,
+    },
+}
+
+#[derive(Clone)]
+pub struct Catch {
+    pub name: Token,
+    pub body: Vec<Stmt>,
+}
+
+pub struct Function {
+    pub name: Token,
+    pub params: Vec<Param>,
+    // A `...name` parameter collecting any extra positional arguments.
+    pub rest: Option<Token>,
+    pub body: Vec<Stmt>,
+}
+
+pub struct Param {
+    pub name: Token,
+    // Evaluated at call time, in the function's scope, when the argument is
+    // left out.
+    pub default: Option<Expr>,
 }
 
 impl Display for LiteralKind {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         let result = match &self {
             LiteralKind::String(s) => format!("String('{s}')"),
+            LiteralKind::Integer(i) => format!("Integer('{i}')"),
+            LiteralKind::BigInteger(i) => format!("BigInteger('{i}')"),
             LiteralKind::Float(f) => format!("Float('{f}')"),
             LiteralKind::Bool(b) => format!("Bool('{b}')"),
-            LiteralKind::Nil => format!("Nil"),
-
+            LiteralKind::Nil => "Nil".into(),
         };
 
         write!(f, "{}", result)
     }
 }
 
-fn parenthesize(name: String, exprs: Vec<&Box<Expr>>) -> String {
+fn parenthesize(name: String, exprs: &[&Expr]) -> String {
     let mut builder = String::new();
 
     builder.push('(');
     builder.push_str(&name);
     for expr in exprs {
         builder.push(' ');
-        builder.push_str(&format!("{}", expr));
+        builder.push_str(&expr.to_string());
     }
     builder.push(')');
 
@@ -63,10 +261,45 @@ fn parenthesize(name: String, exprs: Vec<&Box<Expr>>) -> String {
 impl Display for Expr {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         let result = match &self {
-            Expr::Binary { left, operator, right } => parenthesize(operator.to_string(), vec![left, right]),
-            Expr::Grouping { expr } => parenthesize("group".into(), vec![expr]),
+            Expr::Assign { name, value, .. } => parenthesize(format!("= {}", name.lexeme), &[value]),
+            Expr::Binary { left, operator, right } => parenthesize(operator.to_string(), &[left, right]),
+            Expr::Call { callee, arguments, named, .. } => {
+                let mut exprs = vec![callee.as_ref()];
+                exprs.extend(arguments);
+                let mut call = parenthesize("call".into(), &exprs);
+                for (name, value) in named {
+                    call.insert_str(call.len() - 1, &format!(" {}", parenthesize(format!("{}:", name.lexeme), &[value])));
+                }
+                call
+            },
+            Expr::Conditional { condition, then_branch, else_branch } => {
+                parenthesize("?:".into(), &[condition, then_branch, else_branch])
+            },
+            Expr::Get { object, name } => parenthesize(format!(". {}", name.lexeme), &[object]),
+            Expr::Grouping { expr } => parenthesize("group".into(), &[expr]),
+            Expr::Index { object, index, .. } => parenthesize("index".into(), &[object, index]),
+            Expr::IndexSet { object, index, value, .. } => parenthesize("= index".into(), &[object, index, value]),
+            Expr::Interpolation { parts } => parenthesize("interpolate".into(), &parts.iter().collect::<Vec<&Expr>>()),
+            Expr::Lambda { function } => {
+                let params = function.params.iter().map(|param| param.name.lexeme.as_str()).collect::<Vec<&str>>();
+                format!("(lambda ({}))", params.join(" "))
+            },
+            Expr::List { elements } => parenthesize("list".into(), &elements.iter().collect::<Vec<&Expr>>()),
             Expr::Literal { value } => value.to_string(),
-            Expr::Unary { operator, right } => parenthesize(operator.to_string(), vec![right]),
+            Expr::Map { entries, .. } => {
+                let exprs = entries.iter().flat_map(|(key, value)| [key, value]).collect::<Vec<&Expr>>();
+                parenthesize("map".into(), &exprs)
+            },
+            Expr::Logical { left, operator, right } => parenthesize(operator.to_string(), &[left, right]),
+            Expr::Set { object, name, value } => parenthesize(format!("= .{}", name.lexeme), &[object, value]),
+            Expr::Super { method, .. } => format!("(super {})", method.lexeme),
+            Expr::This { .. } => "this".into(),
+            Expr::Unary { operator, right } => parenthesize(operator.to_string(), &[right]),
+            Expr::Update { target, operator, value, postfix } => {
+                let name = if *postfix { format!("postfix {}", operator.lexeme) } else { operator.lexeme.clone() };
+                parenthesize(name, &[target, value])
+            },
+            Expr::Variable { name, .. } => name.lexeme.clone(),
         };
         write!(f, "{}", result)
     }
@@ -0,0 +1,624 @@
+use std::{cmp::Ordering, fmt::Display};
+
+// An arbitrary-precision integer, used when a result no longer fits in an
+// i64. Stored as a sign and a magnitude of little-endian base 2^32 digits.
+// The magnitude never has leading zero digits, and zero is never negative,
+// so the derived equality and hashing are by value.
+#[derive(Clone, PartialEq, Eq, Hash)]
+pub struct BigInt {
+    negative: bool,
+    magnitude: Vec<u32>,
+}
+
+const DECIMAL_CHUNK: u32 = 1_000_000_000;
+const DECIMAL_CHUNK_DIGITS: usize = 9;
+
+impl BigInt {
+    pub fn zero() -> BigInt {
+        BigInt { negative: false, magnitude: Vec::new() }
+    }
+
+    fn from_parts(negative: bool, mut magnitude: Vec<u32>) -> BigInt {
+        while magnitude.last() == Some(&0) {
+            magnitude.pop();
+        }
+
+        let negative = negative && !magnitude.is_empty();
+        BigInt { negative, magnitude }
+    }
+
+    pub fn from_i64(value: i64) -> BigInt {
+        let unsigned = value.unsigned_abs();
+        BigInt::from_parts(value < 0, vec![unsigned as u32, (unsigned >> 32) as u32])
+    }
+
+    // Exact conversion for floats with an integral value; `None` otherwise.
+    pub fn from_f64(value: f64) -> Option<BigInt> {
+        if !value.is_finite() || value.fract() != 0.0 {
+            return None;
+        }
+
+        let bits = value.to_bits();
+        let exponent = ((bits >> 52) & 0x7ff) as i64;
+        let mantissa = if exponent == 0 {
+            (bits & 0xf_ffff_ffff_ffff) << 1
+        } else {
+            (bits & 0xf_ffff_ffff_ffff) | (1 << 52)
+        };
+
+        // value == mantissa * 2^(exponent - 1075)
+        let shift = exponent - 1075;
+        let magnitude = BigInt::from_parts(false, vec![mantissa as u32, (mantissa >> 32) as u32]);
+        let magnitude = if shift >= 0 {
+            magnitude.shl(shift as usize)
+        } else {
+            // Integral, so the shifted-out bits are all zero.
+            magnitude.shr((-shift) as usize)
+        };
+
+        Some(if value < 0.0 { magnitude.neg() } else { magnitude })
+    }
+
+    pub fn parse(digits: &str) -> Option<BigInt> {
+        let (negative, digits) = match digits.strip_prefix('-') {
+            Some(rest) => (true, rest),
+            None => (false, digits.strip_prefix('+').unwrap_or(digits)),
+        };
+
+        if digits.is_empty() || !digits.bytes().all(|b| b.is_ascii_digit()) {
+            return None;
+        }
+
+        let mut magnitude = Vec::new();
+        let first_chunk = digits.len() % DECIMAL_CHUNK_DIGITS;
+        let mut start = 0;
+        let mut end = if first_chunk == 0 { DECIMAL_CHUNK_DIGITS } else { first_chunk };
+        while start < digits.len() {
+            let chunk: u32 = digits[start..end].parse().ok()?;
+            let multiplier = 10u32.pow((end - start) as u32);
+            mul_small_add(&mut magnitude, multiplier, chunk);
+            start = end;
+            end += DECIMAL_CHUNK_DIGITS;
+        }
+
+        Some(BigInt::from_parts(negative, magnitude))
+    }
+
+    pub fn to_i64(&self) -> Option<i64> {
+        if self.magnitude.len() > 2 {
+            return None;
+        }
+
+        let unsigned = self.magnitude.iter()
+            .rev()
+            .fold(0u64, |acc, &digit| (acc << 32) | digit as u64);
+
+        if self.negative {
+            0i64.checked_sub_unsigned(unsigned)
+        } else {
+            i64::try_from(unsigned).ok()
+        }
+    }
+
+    // Nearest float, or an infinity when out of range. Rounds once, from the
+    // top 64 bits with any set bits below them folded into the lowest one, so
+    // that halfway cases still round the right way.
+    pub fn to_f64(&self) -> f64 {
+        let bits = self.bits();
+        let magnitude = if bits <= 64 {
+            self.low_u64() as f64
+        } else {
+            let shift = bits - 64;
+            let top = self.abs().shr(shift as usize).low_u64();
+            let sticky = self.has_bits_below(shift as usize) as u64;
+            scale((top | sticky) as f64, shift as i64)
+        };
+
+        if self.negative { -magnitude } else { magnitude }
+    }
+
+    // The nearest float to `self / other`, rounded once; an infinity when out
+    // of range. `None` when dividing by zero.
+    pub fn div_to_f64(&self, other: &BigInt) -> Option<f64> {
+        if other.is_zero() {
+            return None;
+        }
+
+        // Scale one side so the quotient has at least 66 bits, then fold a
+        // nonzero remainder into a bit below them for `to_f64` to round with.
+        let exponent = self.bits() as i64 - other.bits() as i64 - 66;
+        let (dividend, divisor) = if exponent < 0 {
+            (self.abs().shl((-exponent) as usize), other.abs())
+        } else {
+            (self.abs(), other.abs().shl(exponent as usize))
+        };
+        let (quotient, remainder) = dividend.div_mod_floor(&divisor)?;
+        let quotient = quotient.shl(1).add(&BigInt::from_i64(!remainder.is_zero() as i64));
+        let magnitude = scale(quotient.to_f64(), exponent - 1);
+
+        Some(if self.negative != other.negative { -magnitude } else { magnitude })
+    }
+
+    fn abs(&self) -> BigInt {
+        BigInt { negative: false, magnitude: self.magnitude.clone() }
+    }
+
+    // The low 64 bits of the magnitude.
+    fn low_u64(&self) -> u64 {
+        let digit = |i: usize| self.magnitude.get(i).copied().unwrap_or(0) as u64;
+        digit(0) | (digit(1) << 32)
+    }
+
+    // Whether any of the magnitude's lowest `bits` bits are set.
+    fn has_bits_below(&self, bits: usize) -> bool {
+        let (digits, bits) = (bits / 32, bits % 32);
+        self.magnitude.iter().take(digits).any(|&digit| digit != 0)
+            || (bits > 0 && self.magnitude.get(digits).is_some_and(|&digit| digit & ((1 << bits) - 1) != 0))
+    }
+
+    // The number of bits in the magnitude, so zero has none.
+    pub fn bits(&self) -> u64 {
+        match self.magnitude.last() {
+            Some(&top) => self.magnitude.len() as u64 * 32 - top.leading_zeros() as u64,
+            None => 0,
+        }
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.magnitude.is_empty()
+    }
+
+    pub fn is_negative(&self) -> bool {
+        self.negative
+    }
+
+    pub fn neg(&self) -> BigInt {
+        BigInt::from_parts(!self.negative, self.magnitude.clone())
+    }
+
+    pub fn add(&self, other: &BigInt) -> BigInt {
+        if self.negative == other.negative {
+            return BigInt::from_parts(self.negative, add_magnitudes(&self.magnitude, &other.magnitude));
+        }
+
+        match compare_magnitudes(&self.magnitude, &other.magnitude) {
+            Ordering::Less => BigInt::from_parts(other.negative, sub_magnitudes(&other.magnitude, &self.magnitude)),
+            _ => BigInt::from_parts(self.negative, sub_magnitudes(&self.magnitude, &other.magnitude)),
+        }
+    }
+
+    pub fn sub(&self, other: &BigInt) -> BigInt {
+        self.add(&other.neg())
+    }
+
+    pub fn mul(&self, other: &BigInt) -> BigInt {
+        BigInt::from_parts(self.negative != other.negative, mul_magnitudes(&self.magnitude, &other.magnitude))
+    }
+
+    // Division rounding toward negative infinity, with a remainder that takes
+    // the divisor's sign, so `a == q * b + r` always holds. `None` when
+    // dividing by zero.
+    pub fn div_mod_floor(&self, other: &BigInt) -> Option<(BigInt, BigInt)> {
+        if other.is_zero() {
+            return None;
+        }
+
+        let (quotient, remainder) = div_rem_magnitudes(&self.magnitude, &other.magnitude);
+        let quotient = BigInt::from_parts(self.negative != other.negative, quotient);
+        let remainder = BigInt::from_parts(self.negative, remainder);
+
+        if !remainder.is_zero() && remainder.negative != other.negative {
+            Some((quotient.sub(&BigInt::from_i64(1)), remainder.add(other)))
+        } else {
+            Some((quotient, remainder))
+        }
+    }
+
+    pub fn pow(&self, mut exponent: u64) -> BigInt {
+        let mut result = BigInt::from_i64(1);
+        let mut base = self.clone();
+        while exponent > 0 {
+            if exponent & 1 == 1 {
+                result = result.mul(&base);
+            }
+            exponent >>= 1;
+            if exponent > 0 {
+                base = base.mul(&base);
+            }
+        }
+
+        result
+    }
+
+    // Multiplies by 2^bits.
+    pub fn shl(&self, bits: usize) -> BigInt {
+        let (digits, bits) = (bits / 32, bits % 32);
+        let mut magnitude = vec![0; digits];
+        let mut carry = 0u32;
+        for &digit in &self.magnitude {
+            let shifted = ((digit as u64) << bits) | carry as u64;
+            magnitude.push(shifted as u32);
+            carry = (shifted >> 32) as u32;
+        }
+        magnitude.push(carry);
+
+        BigInt::from_parts(self.negative, magnitude)
+    }
+
+    // Divides by 2^bits, rounding toward negative infinity like an
+    // arithmetic shift on a two's complement integer.
+    pub fn shr(&self, bits: usize) -> BigInt {
+        if self.negative {
+            // -x >> n == -((x - 1) >> n) - 1
+            let one = BigInt::from_i64(1);
+            return self.neg().sub(&one).shr(bits).neg().sub(&one);
+        }
+
+        let (digits, bits) = (bits / 32, bits % 32);
+        if digits >= self.magnitude.len() {
+            return BigInt::zero();
+        }
+
+        let source = &self.magnitude[digits..];
+        let magnitude = (0..source.len())
+            .map(|i| {
+                let high = source.get(i + 1).copied().unwrap_or(0) as u64;
+                let combined = (high << 32) | source[i] as u64;
+                (combined >> bits) as u32
+            })
+            .collect();
+
+        BigInt::from_parts(self.negative, magnitude)
+    }
+
+    // The bitwise operators act as if on infinitely sign-extended two's
+    // complement integers, like Python's.
+    pub fn not(&self) -> BigInt {
+        self.neg().sub(&BigInt::from_i64(1))
+    }
+
+    pub fn bitand(&self, other: &BigInt) -> BigInt {
+        self.bitwise(other, |a, b| a & b)
+    }
+
+    pub fn bitor(&self, other: &BigInt) -> BigInt {
+        self.bitwise(other, |a, b| a | b)
+    }
+
+    pub fn bitxor(&self, other: &BigInt) -> BigInt {
+        self.bitwise(other, |a, b| a ^ b)
+    }
+
+    fn bitwise(&self, other: &BigInt, op: impl Fn(u32, u32) -> u32) -> BigInt {
+        // One extra digit leaves room for the sign bit.
+        let len = self.magnitude.len().max(other.magnitude.len()) + 1;
+        let (a, b) = (self.twos_complement(len), other.twos_complement(len));
+        let digits = a.iter().zip(&b).map(|(&a, &b)| op(a, b)).collect();
+
+        BigInt::from_twos_complement(digits)
+    }
+
+    fn twos_complement(&self, len: usize) -> Vec<u32> {
+        let mut digits = self.magnitude.clone();
+        digits.resize(len, 0);
+        if self.negative {
+            negate_digits(&mut digits);
+        }
+
+        digits
+    }
+
+    fn from_twos_complement(mut digits: Vec<u32>) -> BigInt {
+        let negative = digits.last().is_some_and(|&digit| digit & 0x8000_0000 != 0);
+        if negative {
+            negate_digits(&mut digits);
+        }
+
+        BigInt::from_parts(negative, digits)
+    }
+}
+
+// Two's complement negation in place: invert every bit and add one.
+fn negate_digits(digits: &mut [u32]) {
+    let mut carry = true;
+    for digit in digits.iter_mut() {
+        (*digit, carry) = (!*digit).overflowing_add(carry as u32);
+    }
+}
+
+// `x * 2^exponent`, split in two so that the power of two itself never
+// overflows or underflows before the result would.
+fn scale(x: f64, exponent: i64) -> f64 {
+    let exponent = exponent.clamp(-2100, 2100) as i32;
+    x * 2f64.powi(exponent / 2) * 2f64.powi(exponent - exponent / 2)
+}
+
+impl Ord for BigInt {
+    fn cmp(&self, other: &BigInt) -> Ordering {
+        match (self.negative, other.negative) {
+            (false, true) => Ordering::Greater,
+            (true, false) => Ordering::Less,
+            (false, false) => compare_magnitudes(&self.magnitude, &other.magnitude),
+            (true, true) => compare_magnitudes(&other.magnitude, &self.magnitude),
+        }
+    }
+}
+
+impl PartialOrd for BigInt {
+    fn partial_cmp(&self, other: &BigInt) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl std::fmt::Debug for BigInt {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{self}")
+    }
+}
+
+impl Display for BigInt {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.is_zero() {
+            return write!(f, "0");
+        }
+
+        // Peel off nine decimal digits at a time, least significant first.
+        let mut chunks = Vec::new();
+        let mut magnitude = self.magnitude.clone();
+        while !magnitude.is_empty() {
+            chunks.push(div_rem_small(&mut magnitude, DECIMAL_CHUNK));
+        }
+
+        if self.negative {
+            write!(f, "-")?;
+        }
+
+        let mut chunks = chunks.iter().rev();
+        write!(f, "{}", chunks.next().unwrap())?;
+        for chunk in chunks {
+            write!(f, "{:0width$}", chunk, width = DECIMAL_CHUNK_DIGITS)?;
+        }
+
+        Ok(())
+    }
+}
+
+fn compare_magnitudes(a: &[u32], b: &[u32]) -> Ordering {
+    a.len().cmp(&b.len())
+        .then_with(|| a.iter().rev().cmp(b.iter().rev()))
+}
+
+fn add_magnitudes(a: &[u32], b: &[u32]) -> Vec<u32> {
+    let (longer, shorter) = if a.len() >= b.len() { (a, b) } else { (b, a) };
+
+    let mut sum = Vec::with_capacity(longer.len() + 1);
+    let mut carry = 0u64;
+    for (i, &digit) in longer.iter().enumerate() {
+        let total = digit as u64 + shorter.get(i).copied().unwrap_or(0) as u64 + carry;
+        sum.push(total as u32);
+        carry = total >> 32;
+    }
+    sum.push(carry as u32);
+
+    sum
+}
+
+// Requires a >= b.
+fn sub_magnitudes(a: &[u32], b: &[u32]) -> Vec<u32> {
+    let mut difference = Vec::with_capacity(a.len());
+    let mut borrow = 0i64;
+    for (i, &digit) in a.iter().enumerate() {
+        let mut total = digit as i64 - b.get(i).copied().unwrap_or(0) as i64 - borrow;
+        borrow = 0;
+        if total < 0 {
+            total += 1 << 32;
+            borrow = 1;
+        }
+        difference.push(total as u32);
+    }
+
+    difference
+}
+
+fn mul_magnitudes(a: &[u32], b: &[u32]) -> Vec<u32> {
+    let mut product = vec![0u32; a.len() + b.len()];
+    for (i, &x) in a.iter().enumerate() {
+        let mut carry = 0u64;
+        for (j, &y) in b.iter().enumerate() {
+            let total = product[i + j] as u64 + x as u64 * y as u64 + carry;
+            product[i + j] = total as u32;
+            carry = total >> 32;
+        }
+        product[i + b.len()] = carry as u32;
+    }
+
+    product
+}
+
+fn mul_small_add(magnitude: &mut Vec<u32>, multiplier: u32, addend: u32) {
+    let mut carry = addend as u64;
+    for digit in magnitude.iter_mut() {
+        let total = *digit as u64 * multiplier as u64 + carry;
+        *digit = total as u32;
+        carry = total >> 32;
+    }
+
+    if carry > 0 {
+        magnitude.push(carry as u32);
+    }
+}
+
+// Divides in place by a single digit, returning the remainder.
+fn div_rem_small(magnitude: &mut Vec<u32>, divisor: u32) -> u32 {
+    let mut remainder = 0u64;
+    for digit in magnitude.iter_mut().rev() {
+        let current = (remainder << 32) | *digit as u64;
+        *digit = (current / divisor as u64) as u32;
+        remainder = current % divisor as u64;
+    }
+
+    while magnitude.last() == Some(&0) {
+        magnitude.pop();
+    }
+
+    remainder as u32
+}
+
+// Truncating division of magnitudes; `b` must be non-zero. Multi-digit
+// divisors use binary long division, which is plenty for script-sized
+// numbers.
+fn div_rem_magnitudes(a: &[u32], b: &[u32]) -> (Vec<u32>, Vec<u32>) {
+    if compare_magnitudes(a, b) == Ordering::Less {
+        return (Vec::new(), a.to_vec());
+    }
+
+    if b.len() == 1 {
+        let mut quotient = a.to_vec();
+        let remainder = div_rem_small(&mut quotient, b[0]);
+        return (quotient, vec![remainder]);
+    }
+
+    let mut quotient = vec![0u32; a.len()];
+    let mut remainder = BigInt::zero();
+    let divisor = BigInt::from_parts(false, b.to_vec());
+    for bit in (0..a.len() * 32).rev() {
+        remainder = remainder.shl(1);
+        if (a[bit / 32] >> (bit % 32)) & 1 == 1 {
+            remainder = remainder.add(&BigInt::from_i64(1));
+        }
+
+        if compare_magnitudes(&remainder.magnitude, &divisor.magnitude) != Ordering::Less {
+            remainder = remainder.sub(&divisor);
+            quotient[bit / 32] |= 1 << (bit % 32);
+        }
+    }
+
+    (quotient, remainder.magnitude)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn big(digits: &str) -> BigInt {
+        BigInt::parse(digits).unwrap()
+    }
+
+    #[test]
+    fn div_mod_floor_rounds_toward_negative_infinity() {
+        let a = "1000000000000000000000000000007";
+        let b = "1000000000003";
+        let cases = [
+            (a.to_string(), b.to_string(), "999999999997000000", "9000007"),
+            (format!("-{a}"), b.to_string(), "-999999999997000001", "999990999996"),
+            (a.to_string(), format!("-{b}"), "-999999999997000001", "-999990999996"),
+            (format!("-{a}"), format!("-{b}"), "999999999997000000", "-9000007"),
+        ];
+
+        for (dividend, divisor, quotient, modulus) in cases {
+            let (q, m) = big(&dividend).div_mod_floor(&big(&divisor)).unwrap();
+            assert_eq!((q, m), (big(quotient), big(modulus)), "{dividend} divided by {divisor}");
+        }
+    }
+
+    #[test]
+    fn div_mod_floor_by_zero() {
+        assert!(big("12345678901234567890").div_mod_floor(&BigInt::zero()).is_none());
+    }
+
+    #[test]
+    fn shr_of_negative_rounds_down() {
+        assert_eq!(big("-5").shr(1), big("-3"));
+        assert_eq!(big("-18446744073709551616").shr(64), big("-1"));
+        assert_eq!(big("-18446744073709551617").shr(64), big("-2"));
+        assert_eq!(big("-1267650600228229401496703205376").shr(1), big("-633825300114114700748351602688"));
+        assert_eq!(big("-1267650600228229401496703205377").shr(200), big("-1"));
+        assert_eq!(big("-1").shr(1000), big("-1"));
+    }
+
+    #[test]
+    fn bitwise_sign_extends_negatives() {
+        let two_pow_64 = big("18446744073709551616");
+        assert_eq!(big("-1").bitand(&two_pow_64), two_pow_64);
+        assert_eq!(two_pow_64.neg().bitand(&big("18446744073709551615")), BigInt::zero());
+        assert_eq!(big("-4294967296").bitor(&big("1")), big("-4294967295"));
+        assert_eq!(big("-1099511627776").bitxor(&big("-1")), big("1099511627775"));
+        assert_eq!(big("-18446744073709551619").bitand(&big("-8589934592")), big("-18446744082299486208"));
+        assert_eq!(big("-18446744073709551619").bitor(&big("1180591620717411303424")), big("-18446744073709551619"));
+        assert_eq!(big("-18446744073709551619").bitxor(&big("4294967301")), big("-18446744078004518920"));
+        assert_eq!(big("-18446744073709551616").not(), big("18446744073709551615"));
+    }
+
+    #[test]
+    fn parse_and_display_round_trip() {
+        let numbers = [
+            "0",
+            "999999999",
+            "1000000000",
+            "1000000001",
+            "999999999999999999",
+            "1000000000000000000",
+            "-1000000000000000000",
+            "123456789012345678901234567",
+            "1000000000000000000000000000",
+            "-99999999999999999999999999999999999999",
+        ];
+
+        for number in numbers {
+            assert_eq!(big(number).to_string(), number);
+        }
+
+        assert_eq!(big("+0001000000000").to_string(), "1000000000");
+        assert_eq!(big("-0").to_string(), "0");
+        assert!(BigInt::parse("12a").is_none());
+        assert!(BigInt::parse("").is_none());
+    }
+
+    #[test]
+    fn from_f64_is_exact_for_integral_floats() {
+        assert_eq!(BigInt::from_f64(9223372036854775808.0), Some(big("9223372036854775808")));
+        assert_eq!(BigInt::from_f64(-(2.0f64).powi(70)), Some(big("-1180591620717411303424")));
+        assert_eq!(BigInt::from_f64(-0.0), Some(BigInt::zero()));
+        assert_eq!(BigInt::from_f64(1.5), None);
+        assert_eq!(BigInt::from_f64(f64::NAN), None);
+        assert_eq!(BigInt::from_f64(f64::INFINITY), None);
+    }
+
+    #[test]
+    fn to_f64_rounds_once() {
+        // 2^117 + 2^64 + 1 is just past halfway between two floats.
+        let two_pow_117 = big("166153499473114484112975882535043072");
+        let two_pow_64 = big("18446744073709551616");
+        let one = BigInt::from_i64(1);
+
+        let above_half = two_pow_117.add(&two_pow_64).add(&one);
+        assert_eq!(above_half.to_f64(), 2f64.powi(117) + 2f64.powi(65));
+        // Exactly halfway ties to even.
+        assert_eq!(two_pow_117.add(&two_pow_64).to_f64(), 2f64.powi(117));
+        assert_eq!(two_pow_117.add(&two_pow_64.mul(&big("3"))).to_f64(), 2f64.powi(117) + 2f64.powi(66));
+        assert_eq!(big("-18446744073709553664").to_f64(), -1.8446744073709552e19);
+        assert_eq!(big("18446744073709553665").to_f64(), 1.8446744073709556e19);
+        assert_eq!(big("1").shl(1024).to_f64(), f64::INFINITY);
+    }
+
+    #[test]
+    fn div_to_f64_rounds_once() {
+        let ten_pow_399 = big("10").pow(399);
+        assert_eq!(ten_pow_399.mul(&big("10")).div_to_f64(&ten_pow_399), Some(10.0));
+        assert_eq!(big("1000000000000000000000000000007").div_to_f64(&big("1000000000003")), Some(9.999999999970001e17));
+        assert_eq!(big("-1000000000000000000000000000007").div_to_f64(&big("1000000000003")), Some(-9.999999999970001e17));
+        assert_eq!(big("12345678901234567891").div_to_f64(&big("3")), Some(4.1152263004115226e18));
+        assert_eq!(big("1").shl(1100).add(&big("1")).div_to_f64(&big("1").shl(1000)), Some(1.2676506002282294e30));
+        assert_eq!(big("-7").div_to_f64(&big("2")), Some(-3.5));
+        assert_eq!(big("1").div_to_f64(&ten_pow_399), Some(0.0));
+        assert_eq!(big("1").shl(1100).div_to_f64(&big("3")), Some(f64::INFINITY));
+        assert_eq!(big("1").div_to_f64(&BigInt::zero()), None);
+    }
+
+    #[test]
+    fn bits_counts_magnitude_bits() {
+        assert_eq!(BigInt::zero().bits(), 0);
+        assert_eq!(big("-1").bits(), 1);
+        assert_eq!(big("4294967296").bits(), 33);
+    }
+}
@@ -1,71 +1,1410 @@
-use crate::{ast::{Expr, LiteralKind}, token::Token, token_type::TokenType};
+use std::{cell::RefCell, cmp::Ordering, collections::HashMap, fmt::Display, rc::Rc};
 
-#[derive(Debug)]
-enum Value {
+use crate::{ast::{Catch, Expr, ExprId, Function, LiteralKind, Stmt}, bigint::BigInt, map::LoxMap, natives, token::Token, token_type::TokenType};
+
+#[derive(Debug, Clone)]
+pub enum Value {
     String(String),
-    Float(f32),
+    Int(i64),
+    // An integer that doesn't fit in an i64. Arithmetic promotes to this on
+    // overflow and demotes back whenever a result fits again.
+    BigInt(BigInt),
+    Float(f64),
     Bool(bool),
+    Callable(Callable),
+    Instance(Rc<RefCell<LoxInstance>>),
+    List(Rc<RefCell<Vec<Value>>>),
+    Map(Rc<RefCell<LoxMap>>),
     Nil,
 }
 
-fn evaluate_grouping(expr: &Expr) -> Value {
-    evaluate_expression(&expr)
+impl Value {
+    pub fn type_name(&self) -> &'static str {
+        match self {
+            Value::String(_) => "string",
+            Value::Int(_) | Value::BigInt(_) => "int",
+            Value::Float(_) => "float",
+            Value::Bool(_) => "bool",
+            Value::Callable(Callable::Class(_)) => "class",
+            Value::Callable(_) => "function",
+            Value::Instance(_) => "instance",
+            Value::List(_) => "list",
+            Value::Map(_) => "map",
+            Value::Nil => "nil",
+        }
+    }
+
+    // `params` name the parameters, for arity errors.
+    pub fn native<F>(name: &str, params: &[&str], function: F) -> Value
+    where
+        F: Fn(&[Value]) -> std::result::Result<Value, (ErrorKind, String)> + 'static,
+    {
+        let params = params.iter().map(|param| param.to_string()).collect();
+        let native = NativeFunction { name: name.into(), params, function: Box::new(function) };
+        Value::Callable(Callable::Native(Rc::new(native)))
+    }
+
+    pub fn list(elements: Vec<Value>) -> Value {
+        Value::List(Rc::new(RefCell::new(elements)))
+    }
+
+    pub fn integer(integer: BigInt) -> Value {
+        match integer.to_i64() {
+            Some(integer) => Value::Int(integer),
+            None => Value::BigInt(integer),
+        }
+    }
+}
+
+// Turns a Lox index into a position in a sequence of `len` elements. Negative
+// indices count back from the end, so -1 is the last element. With
+// `allow_end`, `len` itself is accepted, as when inserting at the end.
+pub(crate) fn sequence_index(index: &Value, len: usize, allow_end: bool) -> std::result::Result<usize, (ErrorKind, String)> {
+    let out_of_range = |i: &dyn Display| (ErrorKind::IndexError, format!("Index {i} out of range for list of length {len}."));
+    let i = match index {
+        Value::Int(i) => i,
+        Value::BigInt(i) => return Err(out_of_range(i)),
+        index => return Err((ErrorKind::TypeError, format!("Index must be an int, not {}.", index.type_name()))),
+    };
+
+    let limit = if allow_end { len + 1 } else { len };
+    let position = if *i < 0 { len as i64 + i } else { *i };
+
+    if position < 0 || position >= limit as i64 {
+        return Err(out_of_range(i));
+    }
+
+    Ok(position as usize)
+}
+
+#[derive(Clone)]
+pub enum Callable {
+    Function(Rc<LoxFunction>),
+    Native(Rc<NativeFunction>),
+    Class(Rc<LoxClass>),
+}
+
+// The evaluated arguments of a call.
+struct Arguments {
+    positional: Vec<Value>,
+    named: Vec<(Token, Value)>,
+}
+
+impl Arguments {
+    fn len(&self) -> usize {
+        self.positional.len() + self.named.len()
+    }
+}
+
+impl Callable {
+    fn call(&self, interpreter: &mut Interpreter, paren: &Token, arguments: Arguments) -> Result<Value> {
+        match self {
+            Callable::Function(function) => function.call(interpreter, paren, &function.declaration.name.lexeme, arguments),
+            Callable::Native(native) => {
+                if let Some((name, _)) = arguments.named.first() {
+                    return Err(RuntimeError::new(name, ErrorKind::ArgumentError, format!("Native function '{}' doesn't take named arguments.", native.name)));
+                }
+
+                if arguments.positional.len() != native.params.len() {
+                    let message = format!(
+                        "{}({}) expects {} but got {}.",
+                        native.name,
+                        native.params.join(", "),
+                        argument_count(native.params.len()),
+                        arguments.positional.len(),
+                    );
+                    return Err(RuntimeError::new(paren, ErrorKind::ArgumentError, message));
+                }
+
+                (native.function)(&arguments.positional)
+                    .map_err(|(kind, message)| RuntimeError::new(paren, kind, message))
+            },
+            Callable::Class(class) => {
+                let instance = Rc::new(RefCell::new(LoxInstance { class: class.clone(), fields: HashMap::new() }));
+                match class.find_method("init") {
+                    Some(Method::Lox(initializer)) => {
+                        initializer.bind(instance.clone()).call(interpreter, paren, &class.name, arguments)?;
+                    },
+                    Some(initializer) => {
+                        initializer.bind(instance.clone(), &class.name).call(interpreter, paren, arguments)?;
+                    },
+                    None if arguments.len() > 0 => {
+                        let message = format!("{}() expects 0 arguments but got {}.", class.name, arguments.len());
+                        return Err(RuntimeError::new(paren, ErrorKind::ArgumentError, message));
+                    },
+                    None => {},
+                }
+
+                Ok(Value::Instance(instance))
+            },
+        }
+    }
+}
+
+impl std::fmt::Debug for Callable {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Callable::Function(function) => write!(f, "<fn {}>", function.declaration.name.lexeme),
+            Callable::Native(native) => write!(f, "<native fn {}>", native.name),
+            Callable::Class(class) => write!(f, "<class {}>", class.name),
+        }
+    }
+}
+
+pub type NativeFn = dyn Fn(&[Value]) -> std::result::Result<Value, (ErrorKind, String)>;
+
+// A function implemented in Rust. The interpreter checks the arity before
+// calling it, and an `Err` is raised as a runtime error of that kind at the call.
+pub struct NativeFunction {
+    name: String,
+    params: Vec<String>,
+    function: Box<NativeFn>,
+}
+
+// "1 argument" or "2 arguments", for arity errors.
+fn argument_count(count: usize) -> String {
+    match count {
+        1 => "1 argument".into(),
+        count => format!("{count} arguments"),
+    }
+}
+
+pub struct LoxFunction {
+    declaration: Rc<Function>,
+    closure: Rc<RefCell<Environment>>,
+    is_initializer: bool,
+}
+
+impl LoxFunction {
+    // `name` is what the function is called by in arity errors, which for an
+    // initializer is its class.
+    fn call(&self, interpreter: &mut Interpreter, paren: &Token, name: &str, arguments: Arguments) -> Result<Value> {
+        if interpreter.depth == MAX_CALL_DEPTH {
+            return Err(RuntimeError::new(paren, ErrorKind::RecursionError, "Stack overflow.".into()));
+        }
+
+        interpreter.depth += 1;
+        let value = self.run(interpreter, paren, name, arguments);
+        interpreter.depth -= 1;
+        value
+    }
+
+    fn run(&self, interpreter: &mut Interpreter, paren: &Token, name: &str, arguments: Arguments) -> Result<Value> {
+        let environment = Rc::new(RefCell::new(Environment::with_enclosing(self.closure.clone())));
+        self.bind_arguments(interpreter, &environment, paren, name, arguments)?;
+
+        let value = match interpreter.execute_block(&self.declaration.body, environment) {
+            Ok(()) => Value::Nil,
+            Err(Interrupt::Return(value)) => value,
+            Err(Interrupt::Error(error)) => return Err(error),
+            // The parser never lets a break/continue reach a function body's edge.
+            Err(Interrupt::Break(_) | Interrupt::Continue(_)) => Value::Nil,
+        };
+
+        // An initializer always hands back the instance it was bound to.
+        if self.is_initializer {
+            return Ok(self.closure.borrow().values.get("this").cloned().unwrap_or(Value::Nil));
+        }
+
+        Ok(value)
+    }
+
+    // Defines every parameter in the call's environment. Positional arguments
+    // fill parameters in order, with any left over going to the rest
+    // parameter; named ones then fill parameters by name; and defaults cover
+    // whatever remains, evaluated in the environment built up so far.
+    fn bind_arguments(
+        &self,
+        interpreter: &mut Interpreter,
+        environment: &Rc<RefCell<Environment>>,
+        paren: &Token,
+        name: &str,
+        arguments: Arguments,
+    ) -> Result<()> {
+        let params = &self.declaration.params;
+        let count = arguments.len();
+        let arity_error = || {
+            let required = params.iter().filter(|param| param.default.is_none()).count();
+            let expected = match (&self.declaration.rest, required == params.len()) {
+                (Some(_), _) => format!("at least {}", argument_count(required)),
+                (None, true) => argument_count(required),
+                (None, false) => format!("{required} to {} arguments", params.len()),
+            };
+            RuntimeError::new(paren, ErrorKind::ArgumentError, format!("{} expects {expected} but got {count}.", self.signature(name)))
+        };
+
+        let mut positional = arguments.positional.into_iter();
+        let mut slots = params.iter().map(|_| positional.next()).collect::<Vec<Option<Value>>>();
+        let extra = positional.collect::<Vec<Value>>();
+        if !extra.is_empty() && self.declaration.rest.is_none() {
+            return Err(arity_error());
+        }
+
+        for (argument, value) in arguments.named {
+            let Some(position) = params.iter().position(|param| param.name.lexeme == argument.lexeme) else {
+                let message = format!("{} has no parameter named '{}'.", self.signature(name), argument.lexeme);
+                return Err(RuntimeError::new(&argument, ErrorKind::ArgumentError, message));
+            };
+
+            if slots[position].is_some() {
+                let message = format!("{} got more than one value for '{}'.", self.signature(name), argument.lexeme);
+                return Err(RuntimeError::new(&argument, ErrorKind::ArgumentError, message));
+            }
+            slots[position] = Some(value);
+        }
+
+        for (param, slot) in params.iter().zip(slots) {
+            let value = match (slot, &param.default) {
+                (Some(value), _) => value,
+                (None, Some(default)) => {
+                    let previous = std::mem::replace(&mut interpreter.environment, environment.clone());
+                    let value = interpreter.evaluate(default);
+                    interpreter.environment = previous;
+                    value?
+                },
+                (None, None) => return Err(arity_error()),
+            };
+            environment.borrow_mut().define(param.name.lexeme.clone(), value);
+        }
+
+        if let Some(rest) = &self.declaration.rest {
+            environment.borrow_mut().define(rest.lexeme.clone(), Value::list(extra));
+        }
+
+        Ok(())
+    }
+
+    // How the function is written, for arity errors: `greet(name, greeting?, ...rest)`
+    // marks parameters with defaults with a '?'.
+    fn signature(&self, name: &str) -> String {
+        let mut params = self.declaration.params.iter()
+            .map(|param| match param.default {
+                Some(_) => format!("{}?", param.name.lexeme),
+                None => param.name.lexeme.clone(),
+            })
+            .collect::<Vec<String>>();
+
+        if let Some(rest) = &self.declaration.rest {
+            params.push(format!("...{}", rest.lexeme));
+        }
+
+        format!("{name}({})", params.join(", "))
+    }
+
+    fn bind(&self, instance: Rc<RefCell<LoxInstance>>) -> LoxFunction {
+        let mut environment = Environment::with_enclosing(self.closure.clone());
+        environment.define("this".into(), Value::Instance(instance));
+
+        LoxFunction {
+            declaration: self.declaration.clone(),
+            closure: Rc::new(RefCell::new(environment)),
+            is_initializer: self.is_initializer,
+        }
+    }
+}
+
+// A method of a class: declared in Lox or, for built-in classes like `Error`,
+// implemented in Rust.
+#[derive(Clone)]
+enum Method {
+    Lox(Rc<LoxFunction>),
+    Native(Rc<NativeMethod>),
+}
+
+type NativeMethodFn = dyn Fn(&Rc<RefCell<LoxInstance>>, &[Value]) -> std::result::Result<Value, (ErrorKind, String)>;
+
+// A method implemented in Rust, called with the instance it's bound to.
+struct NativeMethod {
+    params: Vec<String>,
+    function: Rc<NativeMethodFn>,
+}
+
+impl Method {
+    // Binds `this` to the instance. A native method is called `name` in
+    // arity errors; a Lox one goes by its declared name.
+    fn bind(&self, instance: Rc<RefCell<LoxInstance>>, name: &str) -> Callable {
+        match self {
+            Method::Lox(function) => Callable::Function(Rc::new(function.bind(instance))),
+            Method::Native(method) => {
+                let function = method.function.clone();
+                Callable::Native(Rc::new(NativeFunction {
+                    name: name.into(),
+                    params: method.params.clone(),
+                    function: Box::new(move |arguments| function(&instance, arguments)),
+                }))
+            },
+        }
+    }
+}
+
+pub struct LoxClass {
+    name: String,
+    superclass: Option<Rc<LoxClass>>,
+    methods: HashMap<String, Method>,
+}
+
+impl LoxClass {
+    fn inherits(&self, class: &Rc<LoxClass>) -> bool {
+        std::ptr::eq(self, class.as_ref()) || self.superclass.as_ref().is_some_and(|superclass| superclass.inherits(class))
+    }
+
+    fn find_method(&self, name: &str) -> Option<Method> {
+        match (self.methods.get(name), &self.superclass) {
+            (Some(method), _) => Some(method.clone()),
+            (None, Some(superclass)) => superclass.find_method(name),
+            (None, None) => None,
+        }
+    }
+}
+
+// `Error`'s initializer. The kind is the instance's own class, so that it's
+// right for subclasses too, and the line is filled in when it's thrown.
+fn error_init(instance: &Rc<RefCell<LoxInstance>>, arguments: &[Value]) -> std::result::Result<Value, (ErrorKind, String)> {
+    let mut error = instance.borrow_mut();
+    let kind = Value::String(error.class.name.clone());
+    error.fields.insert("message".into(), arguments[0].clone());
+    error.fields.insert("kind".into(), kind);
+    error.fields.insert("line".into(), Value::Nil);
+
+    Ok(Value::Instance(instance.clone()))
+}
+
+pub struct LoxInstance {
+    class: Rc<LoxClass>,
+    fields: HashMap<String, Value>,
+}
+
+impl LoxInstance {
+    // Fields shadow methods; methods come back bound to this instance.
+    fn get(instance: &Rc<RefCell<LoxInstance>>, name: &Token) -> Result<Value> {
+        if let Some(value) = instance.borrow().fields.get(&name.lexeme) {
+            return Ok(value.clone());
+        }
+
+        let method = instance.borrow().class.find_method(&name.lexeme);
+        match method {
+            Some(method) => Ok(Value::Callable(method.bind(instance.clone(), &name.lexeme))),
+            None => Err(RuntimeError::new(name, ErrorKind::NameError, format!("Undefined property '{}'.", name.lexeme))),
+        }
+    }
+
+    fn set(&mut self, name: &Token, value: Value) {
+        self.fields.insert(name.lexeme.clone(), value);
+    }
+}
+
+impl std::fmt::Debug for LoxInstance {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "<{} instance>", self.class.name)
+    }
+}
+
+impl PartialEq for Value {
+    // Numbers, strings, booleans and nil compare by value; everything else is
+    // a reference and only equals itself.
+    fn eq(&self, other: &Value) -> bool {
+        match (self, other) {
+            (Value::String(a), Value::String(b)) => a == b,
+            (Value::Int(a), Value::Int(b)) => a == b,
+            (Value::Float(a), Value::Float(b)) => a == b,
+            (Value::Int(a), Value::Float(b)) | (Value::Float(b), Value::Int(a)) => compare_int_float(*a, *b) == Some(Ordering::Equal),
+            (Value::BigInt(a), Value::BigInt(b)) => a == b,
+            (Value::BigInt(a), Value::Int(b)) | (Value::Int(b), Value::BigInt(a)) => *a == BigInt::from_i64(*b),
+            (Value::BigInt(a), Value::Float(b)) | (Value::Float(b), Value::BigInt(a)) => BigInt::from_f64(*b).as_ref() == Some(a),
+            (Value::Bool(a), Value::Bool(b)) => a == b,
+            (Value::Nil, Value::Nil) => true,
+            (Value::Callable(a), Value::Callable(b)) => match (a, b) {
+                (Callable::Function(a), Callable::Function(b)) => Rc::ptr_eq(a, b),
+                (Callable::Native(a), Callable::Native(b)) => Rc::ptr_eq(a, b),
+                (Callable::Class(a), Callable::Class(b)) => Rc::ptr_eq(a, b),
+                _ => false,
+            },
+            (Value::Instance(a), Value::Instance(b)) => Rc::ptr_eq(a, b),
+            (Value::List(a), Value::List(b)) => Rc::ptr_eq(a, b),
+            (Value::Map(a), Value::Map(b)) => Rc::ptr_eq(a, b),
+            _ => false,
+        }
+    }
+}
+
+impl Value {
+    // How a value is shown inside a container: strings get quoted so that
+    // `["a, b"]` and `["a", "b"]` print differently.
+    fn element_string(&self) -> String {
+        match self {
+            Value::String(s) => format!("{s:?}"),
+            value => value.to_string(),
+        }
+    }
+
+    // Writes the value as `element_string` would. `seen` holds the lists and
+    // maps being written further up, so a container that holds itself is
+    // shown as `[...]` or `{...}` instead of being written forever.
+    fn write_element(&self, f: &mut std::fmt::Formatter<'_>, seen: &mut Vec<*const ()>) -> std::fmt::Result {
+        let (pointer, placeholder) = match self {
+            Value::List(list) => (Rc::as_ptr(list) as *const (), "[...]"),
+            Value::Map(map) => (Rc::as_ptr(map) as *const (), "{...}"),
+            value => return write!(f, "{}", value.element_string()),
+        };
+
+        if seen.contains(&pointer) {
+            return write!(f, "{placeholder}");
+        }
+
+        seen.push(pointer);
+        match self {
+            Value::List(list) => {
+                write!(f, "[")?;
+                for (i, element) in list.borrow().iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ", ")?;
+                    }
+                    element.write_element(f, seen)?;
+                }
+                write!(f, "]")?;
+            },
+            Value::Map(map) => {
+                write!(f, "{{")?;
+                for (i, (key, value)) in map.borrow().entries().iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ", ")?;
+                    }
+                    key.write_element(f, seen)?;
+                    write!(f, ": ")?;
+                    value.write_element(f, seen)?;
+                }
+                write!(f, "}}")?;
+            },
+            _ => {},
+        }
+        seen.pop();
+
+        Ok(())
+    }
+}
+
+impl Display for Value {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Value::String(s) => write!(f, "{s}"),
+            Value::Int(n) => write!(f, "{n}"),
+            Value::BigInt(n) => write!(f, "{n}"),
+            // Integral floats keep their ".0" so they can't be mistaken for ints.
+            Value::Float(n) if n.is_finite() && n.fract() == 0.0 => write!(f, "{n:.1}"),
+            Value::Float(n) => write!(f, "{n}"),
+            Value::Bool(b) => write!(f, "{b}"),
+            Value::Callable(callable) => write!(f, "{callable:?}"),
+            Value::Instance(instance) => write!(f, "{:?}", instance.borrow()),
+            Value::List(_) | Value::Map(_) => self.write_element(f, &mut Vec::new()),
+            Value::Nil => write!(f, "nil"),
+        }
+    }
+}
+
+// The kinds of error the interpreter itself raises. Lox code sees the kind
+// as the `kind` field of a caught error.
+#[derive(Debug, Clone, Copy)]
+pub enum ErrorKind {
+    Error,
+    ArgumentError,
+    IndexError,
+    KeyError,
+    NameError,
+    OverflowError,
+    RecursionError,
+    TypeError,
+    ValueError,
+    ZeroDivisionError,
+}
+
+impl Display for ErrorKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:?}", self)
+    }
+}
+
+#[derive(Debug)]
+pub struct RuntimeError {
+    pub line: usize,
+    // An `ErrorKind`, or the class name of a thrown error instance.
+    pub kind: String,
+    pub message: String,
+    // What a `throw` statement threw, handed back as-is to a `catch`.
+    thrown: Option<Value>,
+}
+
+impl RuntimeError {
+    fn new(token: &Token, kind: ErrorKind, message: String) -> RuntimeError {
+        RuntimeError { line: token.line, kind: kind.to_string(), message, thrown: None }
+    }
+
+    // Instances of `Error` and its subclasses carry their own message, kind
+    // and line, any of them missing filled in here. Any other value becomes
+    // the message of a plain `Error`.
+    fn thrown(value: Value, keyword: &Token, error_class: &Rc<LoxClass>) -> RuntimeError {
+        let instance = match &value {
+            Value::Instance(instance) if instance.borrow().class.inherits(error_class) => instance.clone(),
+            value => {
+                let message = value.to_string();
+                return RuntimeError { line: keyword.line, kind: ErrorKind::Error.to_string(), message, thrown: Some(value.clone()) };
+            },
+        };
+
+        // Formatting a field may borrow the instance again, if the field
+        // refers back to it, so copy them out before formatting.
+        let (message, kind, line) = {
+            let instance = instance.borrow();
+            let field = |name: &str| instance.fields.get(name).cloned();
+            (field("message"), field("kind"), field("line"))
+        };
+        let message = message.map(|message| message.to_string());
+        let kind = kind.map(|kind| kind.to_string());
+        let line = match line {
+            Some(Value::Int(line)) => usize::try_from(line).ok(),
+            _ => None,
+        };
+
+        let mut instance = instance.borrow_mut();
+        let message = message.unwrap_or_else(|| {
+            instance.fields.insert("message".into(), Value::String(String::new()));
+            String::new()
+        });
+        let line = line.unwrap_or_else(|| {
+            instance.fields.insert("line".into(), Value::Int(keyword.line as i64));
+            keyword.line
+        });
+        let kind = kind.unwrap_or_else(|| {
+            let kind = instance.class.name.clone();
+            instance.fields.insert("kind".into(), Value::String(kind.clone()));
+            kind
+        });
+
+        RuntimeError { line, kind, message, thrown: Some(value) }
+    }
+}
+
+impl Display for RuntimeError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.message.as_str() {
+            "" => write!(f, "{}\n[line {}]", self.kind, self.line),
+            message => write!(f, "{}: {}\n[line {}]", self.kind, message, self.line),
+        }
+    }
+}
+
+type Result<T> = std::result::Result<T, RuntimeError>;
+
+// Anything that stops a statement from running to completion. Loop
+// statements consume the `Break`/`Continue` aimed at them and calls consume
+// `Return`; the parser guarantees loop jumps never escape a function body.
+enum Interrupt {
+    Error(RuntimeError),
+    Break(Option<String>),
+    Continue(Option<String>),
+    Return(Value),
+}
+
+impl From<RuntimeError> for Interrupt {
+    fn from(error: RuntimeError) -> Interrupt {
+        Interrupt::Error(error)
+    }
+}
+
+impl Interrupt {
+    // Whether this break/continue targets the loop with the given label.
+    // An unlabeled one always targets the innermost loop.
+    fn targets(target: &Option<String>, label: &Option<Token>) -> bool {
+        match (target, label) {
+            (None, _) => true,
+            (Some(target), Some(label)) => *target == label.lexeme,
+            (Some(_), None) => false,
+        }
+    }
+}
+
+#[derive(Default)]
+struct Environment {
+    values: HashMap<String, Value>,
+    enclosing: Option<Rc<RefCell<Environment>>>,
+}
+
+impl Environment {
+    fn with_enclosing(enclosing: Rc<RefCell<Environment>>) -> Environment {
+        Environment { values: HashMap::new(), enclosing: Some(enclosing) }
+    }
+
+    fn define(&mut self, name: String, value: Value) {
+        self.values.insert(name, value);
+    }
+
+    fn get(&self, name: &Token) -> Result<Value> {
+        match (self.values.get(&name.lexeme), &self.enclosing) {
+            (Some(value), _) => Ok(value.clone()),
+            (None, Some(enclosing)) => enclosing.borrow().get(name),
+            (None, None) => Err(RuntimeError::new(name, ErrorKind::NameError, format!("Undefined variable '{}'.", name.lexeme))),
+        }
+    }
+
+    fn assign(&mut self, name: &Token, value: Value) -> Result<()> {
+        match (self.values.get_mut(&name.lexeme), &self.enclosing) {
+            (Some(slot), _) => {
+                *slot = value;
+                Ok(())
+            },
+            (None, Some(enclosing)) => enclosing.borrow_mut().assign(name, value),
+            (None, None) => Err(RuntimeError::new(name, ErrorKind::NameError, format!("Undefined variable '{}'.", name.lexeme))),
+        }
+    }
+
+    fn ancestor(environment: &Rc<RefCell<Environment>>, distance: usize) -> Rc<RefCell<Environment>> {
+        let mut environment = environment.clone();
+        for _ in 0..distance {
+            let enclosing = environment.borrow().enclosing.clone()
+                .expect("resolver produced a depth deeper than the scope chain");
+            environment = enclosing;
+        }
+
+        environment
+    }
+}
+
+pub struct Interpreter {
+    globals: Rc<RefCell<Environment>>,
+    environment: Rc<RefCell<Environment>>,
+    // Scope distances for local variable references, filled in by the resolver.
+    locals: HashMap<ExprId, usize>,
+    // The built-in `Error` class, which caught runtime errors are instances of.
+    error_class: Rc<LoxClass>,
+    // How many Lox function calls are in progress.
+    depth: usize,
+}
+
+// Deep enough for any reasonable recursion while staying well inside the
+// interpreter thread's stack.
+const MAX_CALL_DEPTH: usize = 1000;
+
+impl Default for Interpreter {
+    fn default() -> Interpreter {
+        Interpreter::new()
+    }
 }
 
-fn evaluate_unary(operator: &Token, right: &Expr) -> Value {
-    let right = evaluate_expression(right);
+impl Interpreter {
+    pub fn new() -> Interpreter {
+        let globals = Rc::new(RefCell::new(Environment::default()));
+        let init = NativeMethod { params: vec!["message".into()], function: Rc::new(error_init) };
+        let methods = HashMap::from([("init".into(), Method::Native(Rc::new(init)))]);
+        let error_class = Rc::new(LoxClass { name: "Error".into(), superclass: None, methods });
+        globals.borrow_mut().define("Error".into(), Value::Callable(Callable::Class(error_class.clone())));
+
+        let mut interpreter = Interpreter { environment: globals.clone(), globals, locals: HashMap::new(), error_class, depth: 0 };
+        natives::define_builtins(&mut interpreter);
+        interpreter
+    }
+
+    // Makes a Rust function callable from Lox as a global. Natives defined
+    // before `interpret` runs are visible to the whole script.
+    pub fn define_native<F>(&mut self, name: &str, params: &[&str], function: F)
+    where
+        F: Fn(&[Value]) -> std::result::Result<Value, (ErrorKind, String)> + 'static,
+    {
+        self.globals.borrow_mut().define(name.into(), Value::native(name, params, function));
+    }
+
+    pub fn resolve(&mut self, locals: HashMap<ExprId, usize>) {
+        self.locals.extend(locals);
+    }
+
+    pub fn interpret(&mut self, statements: &[Stmt]) -> Result<()> {
+        for statement in statements {
+            if let Err(Interrupt::Error(error)) = self.execute(statement) {
+                return Err(error);
+            }
+        }
+
+        Ok(())
+    }
+
+    // What a catch clause binds: the thrown value itself, or an `Error`
+    // instance describing a runtime error.
+    fn error_value(&self, error: RuntimeError) -> Value {
+        if let Some(value) = error.thrown {
+            return value;
+        }
+
+        let fields = HashMap::from([
+            ("message".into(), Value::String(error.message)),
+            ("kind".into(), Value::String(error.kind)),
+            ("line".into(), Value::Int(error.line as i64)),
+        ]);
+        Value::Instance(Rc::new(RefCell::new(LoxInstance { class: self.error_class.clone(), fields })))
+    }
+
+    fn execute(&mut self, stmt: &Stmt) -> std::result::Result<(), Interrupt> {
+        match stmt {
+            Stmt::Block { statements } => {
+                let environment = Environment::with_enclosing(self.environment.clone());
+                self.execute_block(statements, Rc::new(RefCell::new(environment)))?;
+            },
+            Stmt::Class { name, superclass, methods } => {
+                let superclass = match superclass {
+                    Some(expr) => match self.evaluate(expr)? {
+                        Value::Callable(Callable::Class(class)) => Some(class),
+                        _ => return Err(RuntimeError::new(name, ErrorKind::TypeError, "Superclass must be a class.".into()).into()),
+                    },
+                    None => None,
+                };
+
+                let enclosing = self.environment.clone();
+                if let Some(superclass) = &superclass {
+                    let mut environment = Environment::with_enclosing(enclosing.clone());
+                    environment.define("super".into(), Value::Callable(Callable::Class(superclass.clone())));
+                    self.environment = Rc::new(RefCell::new(environment));
+                }
+
+                let methods = methods.iter()
+                    .map(|method| {
+                        let function = LoxFunction {
+                            declaration: method.clone(),
+                            closure: self.environment.clone(),
+                            is_initializer: method.name.lexeme == "init",
+                        };
+                        (method.name.lexeme.clone(), Method::Lox(Rc::new(function)))
+                    })
+                    .collect();
+
+                let class = LoxClass { name: name.lexeme.clone(), superclass, methods };
+                self.environment = enclosing;
+                self.environment.borrow_mut().define(name.lexeme.clone(), Value::Callable(Callable::Class(Rc::new(class))));
+            },
+            Stmt::Break { label, .. } => {
+                return Err(Interrupt::Break(label.as_ref().map(|label| label.lexeme.clone())));
+            },
+            Stmt::Continue { label, .. } => {
+                return Err(Interrupt::Continue(label.as_ref().map(|label| label.lexeme.clone())));
+            },
+            Stmt::Expression { expr } => {
+                self.evaluate(expr)?;
+            },
+            Stmt::Function { function } => {
+                let callable = Callable::Function(Rc::new(LoxFunction {
+                    declaration: function.clone(),
+                    closure: self.environment.clone(),
+                    is_initializer: false,
+                }));
+                self.environment.borrow_mut().define(function.name.lexeme.clone(), Value::Callable(callable));
+            },
+            Stmt::If { condition, then_branch, else_branch } => {
+                if evaluate_truthy(&self.evaluate(condition)?) {
+                    self.execute(then_branch)?;
+                } else if let Some(else_branch) = else_branch {
+                    self.execute(else_branch)?;
+                }
+            },
+            Stmt::Print { expr } => {
+                let value = self.evaluate(expr)?;
+                println!("{value}");
+            },
+            Stmt::Return { value, .. } => {
+                let value = match value {
+                    Some(expr) => self.evaluate(expr)?,
+                    None => Value::Nil,
+                };
+                return Err(Interrupt::Return(value));
+            },
+            Stmt::Throw { keyword, value } => {
+                let value = self.evaluate(value)?;
+                return Err(RuntimeError::thrown(value, keyword, &self.error_class).into());
+            },
+            Stmt::Try { body, catch, finally } => {
+                let environment = Environment::with_enclosing(self.environment.clone());
+                let result = self.execute_block(body, Rc::new(RefCell::new(environment)));
+                let result = match (result, catch) {
+                    (Err(Interrupt::Error(error)), Some(Catch { name, body })) => {
+                        let mut environment = Environment::with_enclosing(self.environment.clone());
+                        environment.define(name.lexeme.clone(), self.error_value(error));
+                        self.execute_block(body, Rc::new(RefCell::new(environment)))
+                    },
+                    (result, _) => result,
+                };
+
+                // A finally block runs however the rest ended, and anything
+                // it interrupts with replaces what was in flight.
+                if let Some(finally) = finally {
+                    let environment = Environment::with_enclosing(self.environment.clone());
+                    self.execute_block(finally, Rc::new(RefCell::new(environment)))?;
+                }
+
+                result?;
+            },
+            Stmt::Var { name, initializer } => {
+                let value = match initializer {
+                    Some(expr) => self.evaluate(expr)?,
+                    None => Value::Nil,
+                };
+                self.environment.borrow_mut().define(name.lexeme.clone(), value);
+            },
+            Stmt::While { condition, body, increment, label } => {
+                while evaluate_truthy(&self.evaluate(condition)?) {
+                    match self.execute(body) {
+                        Err(Interrupt::Break(target)) if Interrupt::targets(&target, label) => break,
+                        Err(Interrupt::Continue(target)) if Interrupt::targets(&target, label) => {},
+                        result => result?,
+                    }
+
+                    if let Some(increment) = increment {
+                        self.evaluate(increment)?;
+                    }
+                }
+            },
+        }
+
+        Ok(())
+    }
+
+    fn execute_block(&mut self, statements: &[Stmt], environment: Rc<RefCell<Environment>>) -> std::result::Result<(), Interrupt> {
+        let previous = std::mem::replace(&mut self.environment, environment);
+        let result = statements.iter().try_for_each(|statement| self.execute(statement));
+        self.environment = previous;
+        result
+    }
+
+    fn evaluate(&mut self, expr: &Expr) -> Result<Value> {
+        match expr {
+            Expr::Assign { id, name, value } => {
+                let value = self.evaluate(value)?;
+                self.assign_variable(*id, name, value.clone())?;
+                Ok(value)
+            },
+            Expr::Binary { left, operator, right } => self.evaluate_binary(left, operator, right),
+            Expr::Call { callee, paren, arguments, named } => self.evaluate_call(callee, paren, arguments, named),
+            Expr::Conditional { condition, then_branch, else_branch } => {
+                if evaluate_truthy(&self.evaluate(condition)?) {
+                    self.evaluate(then_branch)
+                } else {
+                    self.evaluate(else_branch)
+                }
+            },
+            Expr::Get { object, name } => match self.evaluate(object)? {
+                Value::Instance(instance) => LoxInstance::get(&instance, name),
+                Value::List(list) => natives::list_method(&list, &name.lexeme)
+                    .ok_or_else(|| RuntimeError::new(name, ErrorKind::NameError, format!("Lists have no method '{}'.", name.lexeme))),
+                Value::Map(map) => natives::map_method(&map, &name.lexeme)
+                    .ok_or_else(|| RuntimeError::new(name, ErrorKind::NameError, format!("Maps have no method '{}'.", name.lexeme))),
+                _ => Err(RuntimeError::new(name, ErrorKind::TypeError, "Only instances have properties.".into())),
+            },
+            Expr::Grouping { expr } => self.evaluate(expr),
+            Expr::Index { object, bracket, index } => {
+                let object = self.evaluate(object)?;
+                let index = self.evaluate(index)?;
+                get_index(&object, &index, bracket)
+            },
+            Expr::IndexSet { object, bracket, index, value } => {
+                let object = self.evaluate(object)?;
+                let index = self.evaluate(index)?;
+                let value = self.evaluate(value)?;
+                set_index(&object, index, value.clone(), bracket)?;
+                Ok(value)
+            },
+            Expr::Interpolation { parts } => {
+                let mut string = String::new();
+                for part in parts {
+                    string.push_str(&self.evaluate(part)?.to_string());
+                }
+
+                Ok(Value::String(string))
+            },
+            Expr::Lambda { function } => Ok(Value::Callable(Callable::Function(Rc::new(LoxFunction {
+                declaration: function.clone(),
+                closure: self.environment.clone(),
+                is_initializer: false,
+            })))),
+            Expr::List { elements } => {
+                let elements = elements.iter()
+                    .map(|element| self.evaluate(element))
+                    .collect::<Result<Vec<Value>>>()?;
+                Ok(Value::list(elements))
+            },
+            Expr::Literal { value } => Ok(evaluate_literal(value)),
+            Expr::Map { brace, entries } => {
+                let mut map = LoxMap::default();
+                for (key, value) in entries {
+                    let key = self.evaluate(key)?;
+                    let value = self.evaluate(value)?;
+                    map.insert(key, value).map_err(|message| RuntimeError::new(brace, ErrorKind::TypeError, message))?;
+                }
+
+                Ok(Value::Map(Rc::new(RefCell::new(map))))
+            },
+            Expr::Logical { left, operator, right } => self.evaluate_logical(left, operator, right),
+            Expr::Set { object, name, value } => {
+                let Value::Instance(instance) = self.evaluate(object)? else {
+                    return Err(RuntimeError::new(name, ErrorKind::TypeError, "Only instances have fields.".into()));
+                };
+
+                let value = self.evaluate(value)?;
+                instance.borrow_mut().set(name, value.clone());
+                Ok(value)
+            },
+            Expr::Super { id, keyword, method } => self.evaluate_super(*id, keyword, method),
+            Expr::This { id, keyword } => self.look_up_variable(*id, keyword),
+            Expr::Unary { operator, right } => self.evaluate_unary(operator, right),
+            Expr::Update { target, operator, value, postfix } => self.evaluate_update(target, operator, value, *postfix),
+            Expr::Variable { id, name } => self.look_up_variable(*id, name),
+        }
+    }
+
+    fn assign_variable(&mut self, id: ExprId, name: &Token, value: Value) -> Result<()> {
+        let environment = match self.locals.get(&id) {
+            Some(distance) => Environment::ancestor(&self.environment, *distance),
+            None => self.globals.clone(),
+        };
+
+        let result = environment.borrow_mut().assign(name, value);
+        result
+    }
+
+    // The target's object and index are evaluated once and shared by the
+    // read and the write, so `a[f()] += 1` only calls `f` once.
+    fn evaluate_update(&mut self, target: &Expr, operator: &Token, value: &Expr, postfix: bool) -> Result<Value> {
+        let (old, new) = match target {
+            Expr::Variable { id, name } => {
+                let old = self.look_up_variable(*id, name)?;
+                let new = binary_operation(old.clone(), operator, self.evaluate(value)?)?;
+                self.assign_variable(*id, name, new.clone())?;
+                (old, new)
+            },
+            Expr::Get { object, name } => {
+                let Value::Instance(instance) = self.evaluate(object)? else {
+                    return Err(RuntimeError::new(name, ErrorKind::TypeError, "Only instances have fields.".into()));
+                };
+
+                let old = LoxInstance::get(&instance, name)?;
+                let new = binary_operation(old.clone(), operator, self.evaluate(value)?)?;
+                instance.borrow_mut().set(name, new.clone());
+                (old, new)
+            },
+            Expr::Index { object, bracket, index } => {
+                let object = self.evaluate(object)?;
+                let index = self.evaluate(index)?;
+
+                let old = get_index(&object, &index, bracket)?;
+                let new = binary_operation(old.clone(), operator, self.evaluate(value)?)?;
+                set_index(&object, index, new.clone(), bracket)?;
+                (old, new)
+            },
+            _ => return Err(RuntimeError::new(operator, ErrorKind::TypeError, "Invalid assignment target.".into())),
+        };
+
+        Ok(if postfix { old } else { new })
+    }
+
+    fn look_up_variable(&self, id: ExprId, name: &Token) -> Result<Value> {
+        let environment = match self.locals.get(&id) {
+            Some(distance) => Environment::ancestor(&self.environment, *distance),
+            None => self.globals.clone(),
+        };
+
+        let value = environment.borrow().get(name);
+        value
+    }
+
+    fn evaluate_super(&self, id: ExprId, keyword: &Token, method: &Token) -> Result<Value> {
+        // The resolver puts "super" one scope outside the "this" bound to each method.
+        let distance = self.locals.get(&id).copied().unwrap_or_default();
+        let superclass = Environment::ancestor(&self.environment, distance).borrow().values.get("super").cloned();
+        let object = Environment::ancestor(&self.environment, distance.saturating_sub(1)).borrow().values.get("this").cloned();
+
+        let (Some(Value::Callable(Callable::Class(superclass))), Some(Value::Instance(object))) = (superclass, object) else {
+            return Err(RuntimeError::new(keyword, ErrorKind::TypeError, "Can't use 'super' outside of a subclass method.".into()));
+        };
+
+        match superclass.find_method(&method.lexeme) {
+            Some(found) => Ok(Value::Callable(found.bind(object, &method.lexeme))),
+            None => Err(RuntimeError::new(method, ErrorKind::NameError, format!("Undefined property '{}'.", method.lexeme))),
+        }
+    }
+
+    fn evaluate_call(&mut self, callee: &Expr, paren: &Token, arguments: &[Expr], named: &[(Token, Expr)]) -> Result<Value> {
+        let callee = self.evaluate(callee)?;
+        let positional = arguments.iter()
+            .map(|argument| self.evaluate(argument))
+            .collect::<Result<Vec<Value>>>()?;
+        let named = named.iter()
+            .map(|(name, argument)| Ok((name.clone(), self.evaluate(argument)?)))
+            .collect::<Result<Vec<(Token, Value)>>>()?;
+
+        let callable = match callee {
+            Value::Callable(callable) => callable,
+            _ => return Err(RuntimeError::new(paren, ErrorKind::TypeError, "Can only call functions and classes.".into())),
+        };
+
+        callable.call(self, paren, Arguments { positional, named })
+    }
+
+    fn evaluate_logical(&mut self, left: &Expr, operator: &Token, right: &Expr) -> Result<Value> {
+        let left = self.evaluate(left)?;
+
+        let short_circuits = match &operator.token_type {
+            TokenType::Or => evaluate_truthy(&left),
+            _ => !evaluate_truthy(&left),
+        };
+
+        if short_circuits {
+            Ok(left)
+        } else {
+            self.evaluate(right)
+        }
+    }
+
+    fn evaluate_unary(&mut self, operator: &Token, right: &Expr) -> Result<Value> {
+        let right = self.evaluate(right)?;
+        match &operator.token_type {
+            TokenType::Minus => match right {
+                Value::Int(i) => Ok(i.checked_neg()
+                    .map(Value::Int)
+                    .unwrap_or_else(|| Value::BigInt(BigInt::from_i64(i).neg()))),
+                Value::BigInt(i) => Ok(Value::integer(i.neg())),
+                Value::Float(f) => Ok(Value::Float(-f)),
+                value => Err(RuntimeError::new(operator, ErrorKind::TypeError, format!("Operand of '-' must be a number, not {}.", value.type_name()))),
+            },
+            TokenType::Tilde => match right {
+                Value::Int(i) => Ok(Value::Int(!i)),
+                Value::BigInt(i) => Ok(Value::integer(i.not())),
+                value => Err(RuntimeError::new(operator, ErrorKind::TypeError, format!("Operand of '~' must be an int, not {}.", value.type_name()))),
+            },
+            TokenType::Bang => Ok(Value::Bool(!evaluate_truthy(&right))),
+            token_type => Err(RuntimeError::new(operator, ErrorKind::TypeError, format!("Operator '{}' not supported for {}.", token_type, right.type_name()))),
+        }
+    }
+
+    fn evaluate_binary(&mut self, left: &Expr, operator: &Token, right: &Expr) -> Result<Value> {
+        let left = self.evaluate(left)?;
+        let right = self.evaluate(right)?;
+        binary_operation(left, operator, right)
+    }
+}
+
+// The longest string, in bytes, that repetition will build. Anything bigger
+// would more likely exhaust memory than be meant.
+const MAX_STRING_LENGTH: usize = 1 << 30;
+
+// The most bits an int built by arithmetic may have, about 39,000 decimal
+// digits. Building and printing ints is quadratic in their size, so much
+// larger ones would take seconds or more to compute with.
+pub(crate) const MAX_INTEGER_BITS: u64 = 1 << 17;
+
+fn binary_operation(left: Value, operator: &Token, right: Value) -> Result<Value> {
     match &operator.token_type {
-        TokenType::Minus => match right {
-            Value::Float(f) => Value::Float(f),
-            value => panic!("- not supported for {:?}", value),
+        TokenType::EqEq => return Ok(Value::Bool(left == right)),
+        TokenType::BangEq => return Ok(Value::Bool(left != right)),
+        TokenType::Ampersand | TokenType::Pipe | TokenType::Caret | TokenType::LessLess | TokenType::GreaterGreater => {
+            let is_integer = |value: &Value| matches!(value, Value::Int(_) | Value::BigInt(_));
+            if !is_integer(&left) || !is_integer(&right) {
+                let message = format!("Operands of '{}' must be ints, not {} and {}.", operator.token_type, left.type_name(), right.type_name());
+                return Err(RuntimeError::new(operator, ErrorKind::TypeError, message));
+            }
         },
-        TokenType::Bang => Value::Bool(!evaluate_truthy(&right)),
-        token_type => panic!("({} {:?}) not supported", token_type, right),
+        _ => {},
     }
-}
 
-fn evaluate_binary(left: &Expr, operator: &Token, right: &Expr) -> Value {
-    let left = evaluate_expression(left);
-    let right = evaluate_expression(right);
-    
+    let (left_type, right_type) = (left.type_name(), right.type_name());
+    let is_comparison = matches!(operator.token_type, TokenType::Greater | TokenType::GreaterEq | TokenType::Less | TokenType::LessEq);
+    let unsupported = || {
+        let message = format!("Operator '{}' not supported for {left_type} and {right_type}.", operator.token_type);
+        Err(RuntimeError::new(operator, ErrorKind::TypeError, message))
+    };
+
     match (left, right) {
         (Value::String(left_value), Value::String(right_value)) => match &operator.token_type {
-            TokenType::Plus => Value::String(format!("{left_value} {right_value}")),
-            token_type => panic!("Operator {} not supported for {} and {}", token_type, left_value, right_value)
+            TokenType::Plus => Ok(Value::String(format!("{left_value}{right_value}"))),
+            _ => unsupported(),
         },
 
-        (Value::String(left_value), Value::Float(right_value)) => match &operator.token_type {
-            TokenType::Star => Value::String(left_value.repeat(right_value as usize)),
-            token_type => panic!("Operator {} not supported for {} and {}", token_type, left_value, right_value)
+        (Value::String(string), Value::Int(count)) | (Value::Int(count), Value::String(string)) => match &operator.token_type {
+            TokenType::Star if count < 0 => Err(RuntimeError::new(operator, ErrorKind::ValueError, "Can't repeat a string a negative number of times.".into())),
+            TokenType::Star => match string.len().checked_mul(count as usize) {
+                Some(length) if length <= MAX_STRING_LENGTH => Ok(Value::String(string.repeat(count as usize))),
+                _ => Err(RuntimeError::new(operator, ErrorKind::OverflowError, format!("Can't repeat a string {count} times."))),
+            },
+            _ => unsupported(),
+        },
+        (Value::String(_), Value::BigInt(count)) | (Value::BigInt(count), Value::String(_)) => match &operator.token_type {
+            TokenType::Star if count.is_negative() => Err(RuntimeError::new(operator, ErrorKind::ValueError, "Can't repeat a string a negative number of times.".into())),
+            TokenType::Star => Err(RuntimeError::new(operator, ErrorKind::OverflowError, format!("Can't repeat a string {count} times."))),
+            _ => unsupported(),
         },
 
-        (Value::Float(left_value), Value::String(right_value)) => match &operator.token_type {
-            TokenType::Star => Value::String(right_value.repeat(left_value as usize)),
-            token_type => panic!("Operator {} not supported for {} and {}", token_type, left_value, right_value)
-        }
+        (Value::Int(left_value), Value::Int(right_value)) => evaluate_integer_binary(left_value, operator, right_value),
+        // Comparisons between ints and floats are exact; arithmetic isn't.
+        (Value::Int(left_value), Value::Float(right_value)) if is_comparison => {
+            Ok(compare(compare_int_float(left_value, right_value), operator))
+        },
+        (Value::Float(left_value), Value::Int(right_value)) if is_comparison => {
+            Ok(compare(compare_int_float(right_value, left_value).map(Ordering::reverse), operator))
+        },
+        (Value::BigInt(left_value), Value::Float(right_value)) if is_comparison => {
+            Ok(compare(compare_big_int_float(&left_value, right_value), operator))
+        },
+        (Value::Float(left_value), Value::BigInt(right_value)) if is_comparison => {
+            Ok(compare(compare_big_int_float(&right_value, left_value).map(Ordering::reverse), operator))
+        },
+
+        (Value::Int(left_value), Value::Float(right_value)) => evaluate_float_binary(left_value as f64, operator, right_value),
+        (Value::Float(left_value), Value::Int(right_value)) => evaluate_float_binary(left_value, operator, right_value as f64),
+        (Value::Float(left_value), Value::Float(right_value)) => evaluate_float_binary(left_value, operator, right_value),
+
+        (Value::BigInt(left_value), Value::BigInt(right_value)) => evaluate_big_integer_binary(&left_value, operator, &right_value),
+        (Value::BigInt(left_value), Value::Int(right_value)) => evaluate_big_integer_binary(&left_value, operator, &BigInt::from_i64(right_value)),
+        (Value::Int(left_value), Value::BigInt(right_value)) => evaluate_big_integer_binary(&BigInt::from_i64(left_value), operator, &right_value),
+        (Value::BigInt(left_value), Value::Float(right_value)) => {
+            evaluate_float_binary(big_integer_to_float(&left_value, operator)?, operator, right_value)
+        },
+        (Value::Float(left_value), Value::BigInt(right_value)) => {
+            evaluate_float_binary(left_value, operator, big_integer_to_float(&right_value, operator)?)
+        },
+
+        _ => unsupported(),
+    }
+}
+
+fn get_index(object: &Value, index: &Value, bracket: &Token) -> Result<Value> {
+    match object {
+        Value::List(list) => {
+            let list = list.borrow();
+            let position = sequence_index(index, list.len(), false)
+                .map_err(|(kind, message)| RuntimeError::new(bracket, kind, message))?;
+            Ok(list[position].clone())
+        },
+        Value::Map(map) => match map.borrow().get(index) {
+            Ok(Some(value)) => Ok(value),
+            Ok(None) => Err(RuntimeError::new(bracket, ErrorKind::KeyError, format!("Key {} not found in map.", index.element_string()))),
+            Err(message) => Err(RuntimeError::new(bracket, ErrorKind::TypeError, message)),
+        },
+        value => Err(RuntimeError::new(bracket, ErrorKind::TypeError, format!("Can't index into {}.", value.type_name()))),
+    }
+}
+
+fn set_index(object: &Value, index: Value, value: Value, bracket: &Token) -> Result<()> {
+    match object {
+        Value::List(list) => {
+            let mut list = list.borrow_mut();
+            let position = sequence_index(&index, list.len(), false)
+                .map_err(|(kind, message)| RuntimeError::new(bracket, kind, message))?;
+            list[position] = value;
+            Ok(())
+        },
+        Value::Map(map) => map.borrow_mut().insert(index, value)
+            .map_err(|message| RuntimeError::new(bracket, ErrorKind::TypeError, message)),
+        value => Err(RuntimeError::new(bracket, ErrorKind::TypeError, format!("Can't index into {}.", value.type_name()))),
+    }
+}
+
+// Integers stay integers under `+`, `-`, `*`, `%`, `~/`, `<<` and `**` with
+// a non-negative exponent, switching to a big integer when the result doesn't
+// fit in an i64. `/` always produces a float.
+fn evaluate_integer_binary(left_value: i64, operator: &Token, right_value: i64) -> Result<Value> {
+    let checked = |result: Option<i64>| match result {
+        Some(result) => Ok(Value::Int(result)),
+        None => evaluate_big_integer_binary(&BigInt::from_i64(left_value), operator, &BigInt::from_i64(right_value)),
+    };
+
+    match &operator.token_type {
+        TokenType::Plus => checked(left_value.checked_add(right_value)),
+        TokenType::Minus => checked(left_value.checked_sub(right_value)),
+        TokenType::Star => checked(left_value.checked_mul(right_value)),
+        // Both are exact as floats up to 2^53; past that, divide exactly.
+        TokenType::Slash if left_value.unsigned_abs() <= 1 << 53 && right_value.unsigned_abs() <= 1 << 53 => {
+            evaluate_float_binary(left_value as f64, operator, right_value as f64)
+        },
+        TokenType::Slash => checked(None),
+        TokenType::Percent | TokenType::TildeSlash if right_value == 0 => Err(RuntimeError::new(operator, ErrorKind::ZeroDivisionError, "Division by zero.".into())),
+        TokenType::Percent => checked(floor_div_mod(left_value, right_value).map(|(_, modulus)| modulus)),
+        TokenType::TildeSlash => checked(floor_div_mod(left_value, right_value).map(|(quotient, _)| quotient)),
+        TokenType::StarStar if right_value < 0 => evaluate_float_binary(left_value as f64, operator, right_value as f64),
+        TokenType::StarStar => checked(u32::try_from(right_value).ok().and_then(|exponent| left_value.checked_pow(exponent))),
+        TokenType::Ampersand => Ok(Value::Int(left_value & right_value)),
+        TokenType::Pipe => Ok(Value::Int(left_value | right_value)),
+        TokenType::Caret => Ok(Value::Int(left_value ^ right_value)),
+        TokenType::LessLess | TokenType::GreaterGreater if right_value < 0 => Err(RuntimeError::new(operator, ErrorKind::ValueError, "Negative shift count.".into())),
+        TokenType::LessLess => checked(u32::try_from(right_value).ok()
+            .and_then(|count| left_value.checked_shl(count))
+            .filter(|shifted| shifted >> right_value == left_value)),
+        // Shifting right by 64 or more leaves only copies of the sign bit.
+        TokenType::GreaterGreater => Ok(Value::Int(left_value >> right_value.min(63))),
+        TokenType::Greater => Ok(Value::Bool(left_value > right_value)),
+        TokenType::GreaterEq => Ok(Value::Bool(left_value >= right_value)),
+        TokenType::Less => Ok(Value::Bool(left_value < right_value)),
+        TokenType::LessEq => Ok(Value::Bool(left_value <= right_value)),
+        token_type => Err(RuntimeError::new(operator, ErrorKind::TypeError, format!("Operator '{token_type}' not supported for int and int."))),
+    }
+}
+
+// Division rounding toward negative infinity, with a modulus that takes the
+// divisor's sign, as in Python. `None` on division by zero or overflow.
+fn floor_div_mod(left_value: i64, right_value: i64) -> Option<(i64, i64)> {
+    let quotient = left_value.checked_div(right_value)?;
+    let remainder = left_value.checked_rem(right_value)?;
+
+    if remainder != 0 && (remainder < 0) != (right_value < 0) {
+        Some((quotient - 1, remainder + right_value))
+    } else {
+        Some((quotient, remainder))
+    }
+}
 
-        (Value::Float(left_value), Value::Float(right_value)) => match &operator.token_type {
-                TokenType::Plus => Value::Float(left_value + right_value),
-                TokenType::Minus => Value::Float(left_value - right_value),
-                TokenType::Star => Value::Float(left_value * right_value),
-                TokenType::Slash => Value::Float(left_value / right_value),
-                TokenType::EqEq => Value::Bool(left_value == right_value),
-                TokenType::BangEq => Value::Bool(left_value != right_value),
-                TokenType::Greater => Value::Bool(left_value > right_value),
-                TokenType::GreaterEq => Value::Bool(left_value >= right_value),
-                TokenType::Less => Value::Bool(left_value < right_value),
-                TokenType::LessEq => Value::Bool(left_value <= right_value),
-                token_type => panic!("Operator {} not supported for {} and {}", token_type, left_value, right_value),
+fn evaluate_big_integer_binary(left_value: &BigInt, operator: &Token, right_value: &BigInt) -> Result<Value> {
+    let division = || left_value.div_mod_floor(right_value)
+        .ok_or_else(|| RuntimeError::new(operator, ErrorKind::ZeroDivisionError, "Division by zero.".into()));
+
+    match &operator.token_type {
+        TokenType::Plus => bounded(left_value.add(right_value), operator),
+        TokenType::Minus => bounded(left_value.sub(right_value), operator),
+        TokenType::Star => bounded(left_value.mul(right_value), operator),
+        TokenType::Slash => match left_value.div_to_f64(right_value) {
+            Some(quotient) if quotient.is_finite() => Ok(Value::Float(quotient)),
+            Some(_) => Err(RuntimeError::new(operator, ErrorKind::OverflowError, "Integer division result too large for a float.".into())),
+            // Dividing by zero gives an infinity, as it does for smaller ints.
+            None => evaluate_float_binary(left_value.to_f64(), operator, 0.0),
+        },
+        TokenType::Percent => Ok(Value::integer(division()?.1)),
+        TokenType::TildeSlash => Ok(Value::integer(division()?.0)),
+        TokenType::StarStar if right_value.is_negative() => {
+            evaluate_float_binary(big_integer_to_float(left_value, operator)?, operator, big_integer_to_float(right_value, operator)?)
+        },
+        TokenType::StarStar => match right_value.to_i64() {
+            Some(exponent) if power_bits(left_value, exponent as u64) <= MAX_INTEGER_BITS as f64 => {
+                Ok(Value::integer(left_value.pow(exponent as u64)))
             },
-        (left_value, Value::Nil) => match &operator.token_type {
-            token_type => panic!("Operator {} not supported for {:?} and Nil", token_type, left_value)
+            _ => Err(RuntimeError::new(operator, ErrorKind::OverflowError, format!("Exponent {right_value} is too large."))),
         },
-        (Value::Nil, right_value) => match &operator.token_type {
-            token_type => panic!("Operator {} not supported for Nil and {:?}", token_type, right_value)
+        TokenType::Ampersand => Ok(Value::integer(left_value.bitand(right_value))),
+        TokenType::Pipe => Ok(Value::integer(left_value.bitor(right_value))),
+        TokenType::Caret => Ok(Value::integer(left_value.bitxor(right_value))),
+        TokenType::LessLess | TokenType::GreaterGreater if right_value.is_negative() => {
+            Err(RuntimeError::new(operator, ErrorKind::ValueError, "Negative shift count.".into()))
         },
-        (left_value, right_value) => match &operator.token_type {
-            token_type => panic!("Operator {} not supported for {:?} and {:?}", token_type, left_value, right_value),
-        }
+        TokenType::LessLess if left_value.is_zero() => Ok(Value::Int(0)),
+        TokenType::LessLess => match right_value.to_i64() {
+            Some(count) if left_value.bits().saturating_add(count as u64) <= MAX_INTEGER_BITS => {
+                Ok(Value::integer(left_value.shl(count as usize)))
+            },
+            _ => Err(RuntimeError::new(operator, ErrorKind::OverflowError, format!("Shift count {right_value} is too large."))),
+        },
+        TokenType::GreaterGreater => {
+            let count = right_value.to_i64().and_then(|count| usize::try_from(count).ok()).unwrap_or(usize::MAX);
+            Ok(Value::integer(left_value.shr(count)))
+        },
+        TokenType::Greater => Ok(Value::Bool(left_value > right_value)),
+        TokenType::GreaterEq => Ok(Value::Bool(left_value >= right_value)),
+        TokenType::Less => Ok(Value::Bool(left_value < right_value)),
+        TokenType::LessEq => Ok(Value::Bool(left_value <= right_value)),
+        token_type => Err(RuntimeError::new(operator, ErrorKind::TypeError, format!("Operator '{token_type}' not supported for int and int."))),
+    }
+}
+
+// Orders an int against a float by their exact values. Rounding the int to a
+// float instead would make 2^53 + 1 equal to 2^53. `None` means the float is NaN.
+fn compare_int_float(integer: i64, float: f64) -> Option<Ordering> {
+    // Both are exact as floats up to 2^53.
+    if integer.unsigned_abs() <= 1 << 53 {
+        return (integer as f64).partial_cmp(&float);
+    }
+
+    compare_big_int_float(&BigInt::from_i64(integer), float)
+}
+
+fn compare_big_int_float(integer: &BigInt, float: f64) -> Option<Ordering> {
+    if float.is_nan() {
+        return None;
+    }
+    if float.is_infinite() {
+        return Some(if float > 0.0 { Ordering::Less } else { Ordering::Greater });
+    }
+
+    // Any int above the float's floor is above the float too.
+    let floor = BigInt::from_f64(float.floor())?;
+    Some(match integer.cmp(&floor) {
+        Ordering::Equal if float.fract() != 0.0 => Ordering::Less,
+        ordering => ordering,
+    })
+}
+
+// Applies a comparison operator to an ordering. With a NaN operand there's no
+// ordering, and every comparison is false.
+fn compare(ordering: Option<Ordering>, operator: &Token) -> Value {
+    let result = ordering.is_some_and(|ordering| match operator.token_type {
+        TokenType::Greater => ordering.is_gt(),
+        TokenType::GreaterEq => ordering.is_ge(),
+        TokenType::Less => ordering.is_lt(),
+        _ => ordering.is_le(),
+    });
+    Value::Bool(result)
+}
+
+// Checks that an arithmetic result is within `MAX_INTEGER_BITS`.
+fn bounded(integer: BigInt, operator: &Token) -> Result<Value> {
+    if integer.bits() > MAX_INTEGER_BITS {
+        return Err(RuntimeError::new(operator, ErrorKind::OverflowError, "Integer result too large.".into()));
+    }
+
+    Ok(Value::integer(integer))
+}
+
+// About how many bits `base ** exponent` has, without computing it.
+fn power_bits(base: &BigInt, exponent: u64) -> f64 {
+    // Powers of 0, 1 and -1 never grow.
+    if base.bits() <= 1 {
+        return 0.0;
+    }
+
+    let log2 = match base.to_f64().abs() {
+        float if float.is_finite() => float.log2(),
+        _ => base.bits() as f64,
+    };
+    log2 * exponent as f64
+}
+
+// A big int as a float operand. One beyond the largest float is an error
+// rather than an infinity.
+fn big_integer_to_float(integer: &BigInt, operator: &Token) -> Result<f64> {
+    let float = integer.to_f64();
+    if float.is_finite() {
+        Ok(float)
+    } else {
+        Err(RuntimeError::new(operator, ErrorKind::OverflowError, "Int too large to convert to a float.".into()))
+    }
+}
+
+// Mixed int and float operands are promoted to floats before getting here.
+//
+// `%` and `~/` by zero raise a ZeroDivisionError, as they do for ints. Only
+// `/` follows IEEE 754 and gives an infinity or NaN, for ints as well.
+fn evaluate_float_binary(left_value: f64, operator: &Token, right_value: f64) -> Result<Value> {
+    match &operator.token_type {
+        TokenType::Percent | TokenType::TildeSlash if right_value == 0.0 => {
+            Err(RuntimeError::new(operator, ErrorKind::ZeroDivisionError, "Division by zero.".into()))
+        },
+        TokenType::Plus => Ok(Value::Float(left_value + right_value)),
+        TokenType::Minus => Ok(Value::Float(left_value - right_value)),
+        TokenType::Star => Ok(Value::Float(left_value * right_value)),
+        TokenType::Slash => Ok(Value::Float(left_value / right_value)),
+        TokenType::Percent => {
+            // `%` on floats truncates; shift the result to the divisor's sign.
+            let remainder = left_value % right_value;
+            if remainder != 0.0 && (remainder < 0.0) != (right_value < 0.0) {
+                Ok(Value::Float(remainder + right_value))
+            } else {
+                Ok(Value::Float(remainder))
+            }
+        },
+        TokenType::TildeSlash => Ok(Value::Float((left_value / right_value).floor())),
+        TokenType::StarStar => Ok(Value::Float(left_value.powf(right_value))),
+        TokenType::Greater => Ok(Value::Bool(left_value > right_value)),
+        TokenType::GreaterEq => Ok(Value::Bool(left_value >= right_value)),
+        TokenType::Less => Ok(Value::Bool(left_value < right_value)),
+        TokenType::LessEq => Ok(Value::Bool(left_value <= right_value)),
+        token_type => Err(RuntimeError::new(operator, ErrorKind::TypeError, format!("Operator '{token_type}' not supported for float and float."))),
     }
 }
 
@@ -80,22 +1419,16 @@ fn evaluate_truthy(value: &Value) -> bool {
 fn evaluate_literal(literal: &LiteralKind) -> Value {
     match literal {
         LiteralKind::String(v) => Value::String(v.clone()),
-        LiteralKind::Float(v) => Value::Float(v.clone()),
-        LiteralKind::Bool(v) => Value::Bool(v.clone()),
+        LiteralKind::Integer(v) => Value::Int(*v),
+        LiteralKind::BigInteger(v) => Value::integer(v.clone()),
+        LiteralKind::Float(v) => Value::Float(*v),
+        LiteralKind::Bool(v) => Value::Bool(*v),
         LiteralKind::Nil => Value::Nil,
     }
 }
 
-fn evaluate_expression(expr: &Expr) -> Value {
-    match expr {
-        Expr::Binary { left, operator, right } => evaluate_binary(&left, &operator, &right),
-        Expr::Grouping { expr } => evaluate_grouping(&expr),
-        Expr::Literal { value } => evaluate_literal(value),
-        Expr::Unary { operator, right } => evaluate_unary(operator, right),
-    }
+pub fn interpret(statements: &[Stmt], locals: HashMap<ExprId, usize>) -> Result<()> {
+    let mut interpreter = Interpreter::new();
+    interpreter.resolve(locals);
+    interpreter.interpret(statements)
 }
-
-pub fn interpret(expr: &Expr) {
-    let value = evaluate_expression(&expr);
-    println!("{:?}", value)
-}
\ No newline at end of file
@@ -1,6 +1,17 @@
+pub mod token_type;
+pub mod token;
+pub mod scanner;
+pub mod parser;
+pub mod ast;
+pub mod resolver;
+pub mod interpreter;
+pub mod map;
+pub mod bigint;
+mod natives;
+
 use std::{path::Path, io, fs};
 
 pub fn read_file(path: &Path) -> Result<String, io::Error> {
     let contents = fs::read_to_string(path)?;
     Ok(contents)
-}
\ No newline at end of file
+}
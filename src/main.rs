@@ -1,33 +1,60 @@
-pub mod token_type;
-pub mod token;
-pub mod scanner;
-pub mod parser;
-pub mod ast;
-mod interpreter;
+use std::{env, io::Error, path::{Path, PathBuf}, process, thread};
 
-use std::{env, io::Error, path::Path};
+use rustlox::{interpreter::interpret, parser::Parser, read_file, resolver::Resolver, scanner::Scanner};
 
-
-use interpreter::interpret;
-use parser::Parser;
-use rustlox::read_file;
-
-
-
-use crate::scanner::Scanner;
+// Lox calls recurse on the Rust stack, so the interpreter gets a generous one.
+const STACK_SIZE: usize = 256 * 1024 * 1024;
 
 fn main() -> Result<(), Error> {
     let args: Vec<String> = env::args().collect();
-    let path = Path::new(&args[1]);
+    if args.len() != 2 {
+        eprintln!("Usage: rustlox <script>");
+        process::exit(64);
+    }
+
+    let path = PathBuf::from(&args[1]);
+    thread::Builder::new()
+        .stack_size(STACK_SIZE)
+        .spawn(move || run(&path))?
+        .join()
+        .unwrap_or_else(|_| process::exit(70))
+}
 
-    let contents = read_file(&path)?;
+fn run(path: &Path) -> Result<(), Error> {
+    let contents = read_file(path)?;
     let mut scanner = Scanner::new(contents);
-    scanner.scan_tokens();
+    if let Err(errors) = scanner.scan_tokens() {
+        for error in errors {
+            eprintln!("{error}");
+        }
+        process::exit(65);
+    }
 
     let mut parser = Parser::from_tokens(scanner.tokens);
-    let expr = parser.parse();
-    
-    interpret(&expr);
+    let statements = match parser.parse() {
+        Ok(statements) => statements,
+        Err(errors) => {
+            for error in errors {
+                eprintln!("{error}");
+            }
+            process::exit(65);
+        },
+    };
+
+    let locals = match Resolver::new().resolve(&statements) {
+        Ok(locals) => locals,
+        Err(errors) => {
+            for error in errors {
+                eprintln!("{error}");
+            }
+            process::exit(65);
+        },
+    };
+
+    if let Err(error) = interpret(&statements, locals) {
+        eprintln!("{error}");
+        process::exit(70);
+    }
 
     Ok(())
 }
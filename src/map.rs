@@ -0,0 +1,183 @@
+use std::collections::{hash_map::Entry, HashMap};
+
+use crate::{bigint::BigInt, interpreter::Value};
+
+// The hashable form of a map key. Only strings, numbers and booleans can be
+// keys.
+//
+// Numbers are normalized so that two numbers are the same key exactly when
+// `==` says they're equal, NaN aside:
+//
+// - A float with an integral value is keyed as that integer, so `m[1]` and
+//   `m[1.0]` are the same entry, as `1 == 1.0`. Integers too large for an
+//   `i64` are keyed as big integers, which never overlap with `Integer`. An
+//   int with no exact float, like 2^53 + 1, equals no float and shares no
+//   key with one.
+// - `-0.0` is stored as `0`, since the two compare equal with `==`.
+// - Every other float is keyed by its bit pattern, with every NaN collapsed
+//   into one canonical NaN. Unlike `==`, where NaN never equals itself, a NaN
+//   key can be stored and found again.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+enum MapKey {
+    String(String),
+    Integer(i64),
+    BigInteger(BigInt),
+    Float(u64),
+    Bool(bool),
+}
+
+impl MapKey {
+    fn from_value(value: &Value) -> Result<MapKey, String> {
+        match value {
+            Value::String(s) => Ok(MapKey::String(s.clone())),
+            Value::Int(i) => Ok(MapKey::Integer(*i)),
+            Value::BigInt(i) => Ok(MapKey::BigInteger(i.clone())),
+            Value::Float(f) => Ok(float_key(*f)),
+            Value::Bool(b) => Ok(MapKey::Bool(*b)),
+            value => Err(format!("Map keys must be strings, numbers or booleans, not {}.", value.type_name())),
+        }
+    }
+}
+
+fn float_key(f: f64) -> MapKey {
+    // i64::MAX as f64 rounds up to 2^63, which is out of range, hence `<`.
+    if f.fract() == 0.0 && f >= i64::MIN as f64 && f < i64::MAX as f64 {
+        MapKey::Integer(f as i64)
+    } else if let Some(integer) = BigInt::from_f64(f) {
+        MapKey::BigInteger(integer)
+    } else if f.is_nan() {
+        MapKey::Float(f64::NAN.to_bits())
+    } else {
+        MapKey::Float(f.to_bits())
+    }
+}
+
+// A map that remembers insertion order, so `keys()` and printing are
+// deterministic.
+#[derive(Debug, Default)]
+pub struct LoxMap {
+    entries: Vec<(Value, Value)>,
+    positions: HashMap<MapKey, usize>,
+}
+
+impl LoxMap {
+    pub fn get(&self, key: &Value) -> Result<Option<Value>, String> {
+        let key = MapKey::from_value(key)?;
+        Ok(self.positions.get(&key).map(|&position| self.entries[position].1.clone()))
+    }
+
+    pub fn insert(&mut self, key: Value, value: Value) -> Result<(), String> {
+        match self.positions.entry(MapKey::from_value(&key)?) {
+            Entry::Occupied(entry) => {
+                self.entries[*entry.get()].1 = value;
+            },
+            Entry::Vacant(entry) => {
+                entry.insert(self.entries.len());
+                self.entries.push((key, value));
+            },
+        }
+
+        Ok(())
+    }
+
+    pub fn contains(&self, key: &Value) -> Result<bool, String> {
+        Ok(self.positions.contains_key(&MapKey::from_value(key)?))
+    }
+
+    // Returns whether the key was present.
+    pub fn remove(&mut self, key: &Value) -> Result<bool, String> {
+        let Some(position) = self.positions.remove(&MapKey::from_value(key)?) else {
+            return Ok(false);
+        };
+
+        self.entries.remove(position);
+        for later in self.positions.values_mut() {
+            if *later > position {
+                *later -= 1;
+            }
+        }
+
+        Ok(true)
+    }
+
+    pub fn keys(&self) -> Vec<Value> {
+        self.entries.iter().map(|(key, _)| key.clone()).collect()
+    }
+
+    pub fn values(&self) -> Vec<Value> {
+        self.entries.iter().map(|(_, value)| value.clone()).collect()
+    }
+
+    pub fn entries(&self) -> &[(Value, Value)] {
+        &self.entries
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn get(map: &LoxMap, key: Value) -> Option<Value> {
+        map.get(&key).unwrap()
+    }
+
+    #[test]
+    fn nan_key_can_be_found_again() {
+        let mut map = LoxMap::default();
+        map.insert(Value::Float(f64::NAN), Value::Int(1)).unwrap();
+
+        assert_eq!(get(&map, Value::Float(f64::NAN)), Some(Value::Int(1)));
+        assert_eq!(get(&map, Value::Float(-f64::NAN)), Some(Value::Int(1)));
+        assert_eq!(map.keys().len(), 1);
+    }
+
+    #[test]
+    fn negative_zero_is_zero() {
+        let mut map = LoxMap::default();
+        map.insert(Value::Float(-0.0), Value::Int(1)).unwrap();
+        map.insert(Value::Int(0), Value::Int(2)).unwrap();
+
+        assert_eq!(map.entries().len(), 1);
+        assert_eq!(get(&map, Value::Float(0.0)), Some(Value::Int(2)));
+        assert_eq!(get(&map, Value::Float(-0.0)), Some(Value::Int(2)));
+    }
+
+    #[test]
+    fn integral_float_is_its_integer() {
+        let mut map = LoxMap::default();
+        map.insert(Value::Int(1), Value::Int(1)).unwrap();
+        map.insert(Value::Float(1.0), Value::Int(2)).unwrap();
+
+        assert_eq!(map.entries().len(), 1);
+        assert_eq!(get(&map, Value::Int(1)), Some(Value::Int(2)));
+        assert_eq!(get(&map, Value::Float(1.5)), None);
+    }
+
+    #[test]
+    fn huge_integral_float_finds_big_integer() {
+        let two_pow_63 = BigInt::parse("9223372036854775808").unwrap();
+        let mut map = LoxMap::default();
+        map.insert(Value::BigInt(two_pow_63), Value::Int(1)).unwrap();
+
+        assert_eq!(get(&map, Value::Float(9223372036854775808.0)), Some(Value::Int(1)));
+        assert_eq!(get(&map, Value::Float(i64::MIN as f64)), None);
+    }
+
+    #[test]
+    fn nearby_int_and_float_stay_apart() {
+        // 2^53 + 1 has no exact float, so it's neither equal to nor keyed
+        // with the float 2^53.
+        let mut map = LoxMap::default();
+        map.insert(Value::Int(9007199254740993), Value::Int(1)).unwrap();
+
+        assert_ne!(Value::Int(9007199254740993), Value::Float(9007199254740992.0));
+        assert_eq!(get(&map, Value::Float(9007199254740992.0)), None);
+    }
+
+    #[test]
+    fn unhashable_keys_are_rejected() {
+        let mut map = LoxMap::default();
+        assert!(map.insert(Value::Nil, Value::Int(1)).is_err());
+        assert!(map.get(&Value::list(Vec::new())).is_err());
+    }
+}
@@ -0,0 +1,95 @@
+use std::{cell::RefCell, rc::Rc, time::{SystemTime, UNIX_EPOCH}};
+
+use crate::{bigint::BigInt, interpreter::{sequence_index, ErrorKind, Interpreter, Value, MAX_INTEGER_BITS}, map::LoxMap};
+
+// Seeds the global environment with the natives every script can rely on.
+pub fn define_builtins(interpreter: &mut Interpreter) {
+    interpreter.define_native("clock", &[], clock);
+    interpreter.define_native("str", &["value"], str);
+    interpreter.define_native("num", &["value"], num);
+    interpreter.define_native("type", &["value"], type_);
+}
+
+fn clock(_: &[Value]) -> Result<Value, (ErrorKind, String)> {
+    let elapsed = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_err(|error| (ErrorKind::Error, error.to_string()))?;
+
+    Ok(Value::Float(elapsed.as_secs_f64()))
+}
+
+fn str(arguments: &[Value]) -> Result<Value, (ErrorKind, String)> {
+    Ok(Value::String(arguments[0].to_string()))
+}
+
+// As many digits as the largest int the interpreter builds has.
+const MAX_INTEGER_DIGITS: usize = (MAX_INTEGER_BITS as f64 * std::f64::consts::LOG10_2) as usize + 1;
+
+fn num(arguments: &[Value]) -> Result<Value, (ErrorKind, String)> {
+    match &arguments[0] {
+        Value::Int(_) | Value::BigInt(_) | Value::Float(_) => Ok(arguments[0].clone()),
+        // Parsing is quadratic in the number of digits, like printing.
+        Value::String(s) if s.trim().len() > MAX_INTEGER_DIGITS => {
+            Err((ErrorKind::OverflowError, format!("Can't convert a string of more than {MAX_INTEGER_DIGITS} digits to a number.")))
+        },
+        Value::String(s) => match BigInt::parse(s.trim()) {
+            Some(integer) => Ok(Value::integer(integer)),
+            None => s.trim().parse()
+                .map(Value::Float)
+                .map_err(|_| (ErrorKind::ValueError, format!("Can't convert '{s}' to a number."))),
+        },
+        value => Err((ErrorKind::TypeError, format!("Can't convert {} to a number.", value.type_name()))),
+    }
+}
+
+fn type_(arguments: &[Value]) -> Result<Value, (ErrorKind, String)> {
+    Ok(Value::String(arguments[0].type_name().into()))
+}
+
+// Looks up a built-in list method, bound to `list` so that calls mutate it in
+// place and every alias of the list sees the change.
+pub(crate) fn list_method(list: &Rc<RefCell<Vec<Value>>>, name: &str) -> Option<Value> {
+    let list = list.clone();
+    let method = match name {
+        "len" => Value::native(name, &[], move |_| Ok(Value::Int(list.borrow().len() as i64))),
+        "push" => Value::native(name, &["element"], move |arguments| {
+            list.borrow_mut().push(arguments[0].clone());
+            Ok(Value::Nil)
+        }),
+        "pop" => Value::native(name, &[], move |_| {
+            list.borrow_mut().pop().ok_or_else(|| (ErrorKind::IndexError, "Can't pop from an empty list.".to_string()))
+        }),
+        "insert" => Value::native(name, &["index", "element"], move |arguments| {
+            let mut list = list.borrow_mut();
+            let position = sequence_index(&arguments[0], list.len(), true)?;
+            list.insert(position, arguments[1].clone());
+            Ok(Value::Nil)
+        }),
+        "remove" => Value::native(name, &["index"], move |arguments| {
+            let mut list = list.borrow_mut();
+            let position = sequence_index(&arguments[0], list.len(), false)?;
+            Ok(list.remove(position))
+        }),
+        _ => return None,
+    };
+
+    Some(method)
+}
+
+// Looks up a built-in map method, bound to `map` like the list methods.
+pub(crate) fn map_method(map: &Rc<RefCell<LoxMap>>, name: &str) -> Option<Value> {
+    let map = map.clone();
+    let method = match name {
+        "keys" => Value::native(name, &[], move |_| Ok(Value::list(map.borrow().keys()))),
+        "values" => Value::native(name, &[], move |_| Ok(Value::list(map.borrow().values()))),
+        "has" => Value::native(name, &["key"], move |arguments| {
+            map.borrow().contains(&arguments[0]).map(Value::Bool).map_err(|message| (ErrorKind::TypeError, message))
+        }),
+        "delete" => Value::native(name, &["key"], move |arguments| {
+            map.borrow_mut().remove(&arguments[0]).map(Value::Bool).map_err(|message| (ErrorKind::TypeError, message))
+        }),
+        _ => return None,
+    };
+
+    Some(method)
+}
@@ -1,39 +1,530 @@
-use std::fmt::Display;
+use std::{fmt::Display, rc::Rc};
 
-use crate::{token::Token, ast::{Expr, LiteralKind}, token_type::TokenType};
+use crate::{token::Token, ast::{Catch, Expr, ExprId, Function, LiteralKind, Param, Stmt}, token_type::TokenType};
+
+#[derive(Debug)]
+pub struct ParseError {
+    pub line: usize,
+    pub location: String,
+    pub message: String,
+}
+
+impl ParseError {
+    fn new(token: &Token, message: &str) -> ParseError {
+        let location = match token.token_type {
+            TokenType::EOF => " at end".into(),
+            _ => format!(" at '{}'", token.lexeme),
+        };
+
+        ParseError { line: token.line, location, message: message.into() }
+    }
+}
+
+impl Display for ParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "[line {}] Error{}: {}", self.line, self.location, self.message)
+    }
+}
+
+type Result<T> = std::result::Result<T, ParseError>;
 
 pub struct Parser {
     tokens: Vec<Token>,
     current: u32,
+    errors: Vec<ParseError>,
+    // Labels of the loops enclosing the statement being parsed, innermost last.
+    loops: Vec<Option<String>>,
+    next_id: ExprId,
 }
 
 impl Parser {
-    pub fn parse(&mut self) -> Expr {
-        self.expression()
+    pub fn parse(&mut self) -> std::result::Result<Vec<Stmt>, Vec<ParseError>> {
+        let mut statements = Vec::new();
+        while !self.is_at_end() {
+            if let Some(statement) = self.declaration() {
+                statements.push(statement);
+            }
+        }
+
+        if self.errors.is_empty() {
+            Ok(statements)
+        } else {
+            Err(std::mem::take(&mut self.errors))
+        }
     }
 
     pub fn from_tokens(tokens: Vec<Token>) -> Parser {
-        Parser { tokens, current: 0 }
+        Parser { tokens, current: 0, errors: Vec::new(), loops: Vec::new(), next_id: 0 }
+    }
+
+    fn declaration(&mut self) -> Option<Stmt> {
+        let result = if self.match_(TokenType::Class) {
+            self.class_declaration()
+        } else if self.check(TokenType::Fun) && self.check_next_identifier() {
+            // Without a name, `fun` starts a lambda in an expression statement.
+            self.advance();
+            self.function("function").map(|function| Stmt::Function { function })
+        } else if self.match_(TokenType::Var) {
+            self.var_declaration()
+        } else {
+            self.statement()
+        };
+
+        match result {
+            Ok(statement) => Some(statement),
+            Err(error) => {
+                self.errors.push(error);
+                self.synchronize();
+                None
+            },
+        }
+    }
+
+    fn class_declaration(&mut self) -> Result<Stmt> {
+        let name = self.consume_identifier("Expected class name.")?;
+
+        let superclass = if self.match_(TokenType::Less) {
+            let name = self.consume_identifier("Expected superclass name.")?;
+            Some(Expr::Variable { id: self.next_id(), name })
+        } else {
+            None
+        };
+
+        self.consume(TokenType::LeftBrace, "Expected '{' before class body.")?;
+
+        let mut methods = Vec::new();
+        while !self.check(TokenType::RightBrace) && !self.is_at_end() {
+            methods.push(self.function("method")?);
+        }
+
+        self.consume(TokenType::RightBrace, "Expected '}' after class body.")?;
+        Ok(Stmt::Class { name, superclass, methods })
+    }
+
+    fn function(&mut self, kind: &str) -> Result<Rc<Function>> {
+        let name = self.consume_identifier(&format!("Expected {kind} name."))?;
+        self.consume(TokenType::LeftParen, &format!("Expected '(' after {kind} name."))?;
+        let (params, rest) = self.parameters()?;
+        let body = self.function_body(kind)?;
+
+        Ok(Rc::new(Function { name, params, rest, body }))
+    }
+
+    // Parses a parameter list up to and including the closing ')'. Returns
+    // the ordinary parameters and the rest parameter, if any.
+    fn parameters(&mut self) -> Result<(Vec<Param>, Option<Token>)> {
+        let mut params: Vec<Param> = Vec::new();
+        let mut rest = None;
+        if !self.check(TokenType::RightParen) {
+            loop {
+                if self.match_(TokenType::Ellipsis) {
+                    rest = Some(self.consume_identifier("Expected rest parameter name after '...'.")?);
+                    if self.check(TokenType::Comma) {
+                        return Err(ParseError::new(self.peek().unwrap(), "Rest parameter must be last."));
+                    }
+                    break;
+                }
+
+                let name = self.consume_identifier("Expected parameter name.")?;
+                let default = if self.match_(TokenType::Eq) {
+                    Some(self.expression()?)
+                } else {
+                    if params.last().is_some_and(|param| param.default.is_some()) {
+                        self.error(&name, "Parameter without a default can't follow one with a default.");
+                    }
+                    None
+                };
+                params.push(Param { name, default });
+
+                if !self.match_(TokenType::Comma) {
+                    break;
+                }
+            }
+        }
+        self.consume(TokenType::RightParen, "Expected ')' after parameters.")?;
+
+        Ok((params, rest))
+    }
+
+    fn function_body(&mut self, kind: &str) -> Result<Vec<Stmt>> {
+        self.consume(TokenType::LeftBrace, &format!("Expected '{{' before {kind} body."))?;
+        // Loops outside the function can't be targeted from inside it.
+        let loops = std::mem::take(&mut self.loops);
+        let body = self.block();
+        self.loops = loops;
+
+        body
+    }
+
+    // `fun (params) { body }`, after the `fun`.
+    fn lambda(&mut self, keyword: &Token) -> Result<Expr> {
+        self.consume(TokenType::LeftParen, "Expected '(' after 'fun'.")?;
+        let (params, rest) = self.parameters()?;
+        let body = self.function_body("function")?;
+
+        Ok(Expr::Lambda { function: Rc::new(Function { name: lambda_name(keyword), params, rest, body }) })
+    }
+
+    // `(params) => expression`, after the '('. The body is an expression
+    // whose value is returned.
+    fn arrow_lambda(&mut self) -> Result<Expr> {
+        let (params, rest) = self.parameters()?;
+        let arrow = self.consume(TokenType::Arrow, "Expected '=>' after parameters.")?;
+        let value = self.expression()?;
+
+        let body = vec![Stmt::Return { keyword: arrow.clone(), value: Some(value) }];
+        Ok(Expr::Lambda { function: Rc::new(Function { name: lambda_name(&arrow), params, rest, body }) })
+    }
+
+    fn var_declaration(&mut self) -> Result<Stmt> {
+        let name = self.consume_identifier("Expected variable name.")?;
+
+        let initializer = if self.match_(TokenType::Eq) {
+            Some(self.expression()?)
+        } else {
+            None
+        };
+
+        self.consume(TokenType::Semicolon, "Expected ';' after variable declaration.")?;
+        Ok(Stmt::Var { name, initializer })
+    }
+
+    fn statement(&mut self) -> Result<Stmt> {
+        if self.match_(TokenType::Break) {
+            self.break_statement()
+        } else if self.match_(TokenType::Continue) {
+            self.continue_statement()
+        } else if self.match_(TokenType::For) {
+            self.for_statement(None)
+        } else if self.match_(TokenType::If) {
+            self.if_statement()
+        } else if self.match_(TokenType::Print) {
+            self.print_statement()
+        } else if self.match_(TokenType::Return) {
+            self.return_statement()
+        } else if self.match_(TokenType::Throw) {
+            self.throw_statement()
+        } else if self.match_(TokenType::Try) {
+            self.try_statement()
+        } else if self.match_(TokenType::While) {
+            self.while_statement(None)
+        } else if self.check(TokenType::LeftBrace) && !self.check_map_literal() {
+            self.advance();
+            Ok(Stmt::Block { statements: self.block()? })
+        } else if self.check_identifier() && self.check_next(TokenType::Colon) {
+            self.labeled_statement()
+        } else {
+            self.expression_statement()
+        }
+    }
+
+    fn block(&mut self) -> Result<Vec<Stmt>> {
+        let mut statements = Vec::new();
+        while !self.check(TokenType::RightBrace) && !self.is_at_end() {
+            if let Some(statement) = self.declaration() {
+                statements.push(statement);
+            }
+        }
+
+        self.consume(TokenType::RightBrace, "Expected '}' after block.")?;
+        Ok(statements)
+    }
+
+    fn labeled_statement(&mut self) -> Result<Stmt> {
+        let label = self.consume_identifier("Expected label.")?;
+        self.consume(TokenType::Colon, "Expected ':' after label.")?;
+
+        if self.loops.contains(&Some(label.lexeme.clone())) {
+            self.error(&label, &format!("Label '{}' is already in use by an enclosing loop.", label.lexeme));
+        }
+
+        if self.match_(TokenType::For) {
+            self.for_statement(Some(label))
+        } else if self.match_(TokenType::While) {
+            self.while_statement(Some(label))
+        } else {
+            Err(ParseError::new(self.peek().unwrap(), "Expected loop after label."))
+        }
+    }
+
+    fn break_statement(&mut self) -> Result<Stmt> {
+        let keyword = self.previous().unwrap().clone();
+        let label = self.loop_label(&keyword)?;
+        self.consume(TokenType::Semicolon, "Expected ';' after 'break'.")?;
+        Ok(Stmt::Break { keyword, label })
+    }
+
+    fn continue_statement(&mut self) -> Result<Stmt> {
+        let keyword = self.previous().unwrap().clone();
+        let label = self.loop_label(&keyword)?;
+        self.consume(TokenType::Semicolon, "Expected ';' after 'continue'.")?;
+        Ok(Stmt::Continue { keyword, label })
+    }
+
+    // Parses the optional label after `break`/`continue` and checks that it
+    // refers to an enclosing loop.
+    fn loop_label(&mut self, keyword: &Token) -> Result<Option<Token>> {
+        let label = if self.check_identifier() {
+            Some(self.consume_identifier("Expected label.")?)
+        } else {
+            None
+        };
+
+        match &label {
+            _ if self.loops.is_empty() => {
+                self.error(keyword, &format!("Can't use '{}' outside of a loop.", keyword.lexeme));
+            },
+            Some(label) if !self.loops.contains(&Some(label.lexeme.clone())) => {
+                self.error(label, &format!("No enclosing loop is labeled '{}'.", label.lexeme));
+            },
+            _ => {},
+        }
+
+        Ok(label)
+    }
+
+    fn for_statement(&mut self, label: Option<Token>) -> Result<Stmt> {
+        // Desugared into `{ init; while (cond) body }`, with incr kept on the
+        // loop so that `continue` still runs it.
+        self.consume(TokenType::LeftParen, "Expected '(' after 'for'.")?;
+
+        let initializer = if self.match_(TokenType::Semicolon) {
+            None
+        } else if self.match_(TokenType::Var) {
+            Some(self.var_declaration()?)
+        } else {
+            Some(self.expression_statement()?)
+        };
+
+        let condition = if self.check(TokenType::Semicolon) {
+            Expr::Literal { value: LiteralKind::Bool(true) }
+        } else {
+            self.expression()?
+        };
+        self.consume(TokenType::Semicolon, "Expected ';' after loop condition.")?;
+
+        let increment = if self.check(TokenType::RightParen) {
+            None
+        } else {
+            Some(self.expression()?)
+        };
+        self.consume(TokenType::RightParen, "Expected ')' after for clauses.")?;
+
+        let body = self.loop_body(&label)?.into();
+        let mut stmt = Stmt::While { condition, body, increment, label };
+
+        if let Some(initializer) = initializer {
+            stmt = Stmt::Block { statements: vec![initializer, stmt] };
+        }
+
+        Ok(stmt)
+    }
+
+    fn if_statement(&mut self) -> Result<Stmt> {
+        self.consume(TokenType::LeftParen, "Expected '(' after 'if'.")?;
+        let condition = self.expression()?;
+        self.consume(TokenType::RightParen, "Expected ')' after if condition.")?;
+
+        let then_branch = self.statement()?.into();
+        // Binding the else eagerly attaches it to the nearest if.
+        let else_branch = if self.match_(TokenType::Else) {
+            Some(self.statement()?.into())
+        } else {
+            None
+        };
+
+        Ok(Stmt::If { condition, then_branch, else_branch })
+    }
+
+    fn while_statement(&mut self, label: Option<Token>) -> Result<Stmt> {
+        self.consume(TokenType::LeftParen, "Expected '(' after 'while'.")?;
+        let condition = self.expression()?;
+        self.consume(TokenType::RightParen, "Expected ')' after condition.")?;
+        let body = self.loop_body(&label)?.into();
+
+        Ok(Stmt::While { condition, body, increment: None, label })
+    }
+
+    fn loop_body(&mut self, label: &Option<Token>) -> Result<Stmt> {
+        self.loops.push(label.as_ref().map(|label| label.lexeme.clone()));
+        let body = self.statement();
+        self.loops.pop();
+        body
+    }
+
+    fn print_statement(&mut self) -> Result<Stmt> {
+        let expr = self.expression()?;
+        self.consume(TokenType::Semicolon, "Expected ';' after value.")?;
+        Ok(Stmt::Print { expr })
+    }
+
+    fn return_statement(&mut self) -> Result<Stmt> {
+        let keyword = self.previous().unwrap().clone();
+        let value = if self.check(TokenType::Semicolon) {
+            None
+        } else {
+            Some(self.expression()?)
+        };
+
+        self.consume(TokenType::Semicolon, "Expected ';' after return value.")?;
+        Ok(Stmt::Return { keyword, value })
     }
 
-    fn expression(&mut self) -> Expr {
-        self.equality()
+    fn throw_statement(&mut self) -> Result<Stmt> {
+        let keyword = self.previous().unwrap().clone();
+        let value = self.expression()?;
+        self.consume(TokenType::Semicolon, "Expected ';' after thrown value.")?;
+        Ok(Stmt::Throw { keyword, value })
+    }
+
+    fn try_statement(&mut self) -> Result<Stmt> {
+        let keyword = self.previous().unwrap().clone();
+        self.consume(TokenType::LeftBrace, "Expected '{' after 'try'.")?;
+        let body = self.block()?;
+
+        let catch = if self.match_(TokenType::Catch) {
+            self.consume(TokenType::LeftParen, "Expected '(' after 'catch'.")?;
+            let name = self.consume_identifier("Expected error variable name.")?;
+            self.consume(TokenType::RightParen, "Expected ')' after error variable.")?;
+            self.consume(TokenType::LeftBrace, "Expected '{' before catch body.")?;
+            Some(Catch { name, body: self.block()? })
+        } else {
+            None
+        };
+
+        let finally = if self.match_(TokenType::Finally) {
+            self.consume(TokenType::LeftBrace, "Expected '{' after 'finally'.")?;
+            Some(self.block()?)
+        } else {
+            None
+        };
+
+        if catch.is_none() && finally.is_none() {
+            self.error(&keyword, "Expected 'catch' or 'finally' after try block.");
+        }
+
+        Ok(Stmt::Try { body, catch, finally })
     }
 
-    fn equality(&mut self) -> Expr {
-        let mut expr = self.comparision();
+    fn expression_statement(&mut self) -> Result<Stmt> {
+        let expr = self.expression()?;
+        self.consume(TokenType::Semicolon, "Expected ';' after expression.")?;
+        Ok(Stmt::Expression { expr })
+    }
+
+    fn expression(&mut self) -> Result<Expr> {
+        self.assignment()
+    }
+
+    fn assignment(&mut self) -> Result<Expr> {
+        let expr = self.conditional()?;
+
+        if self.match_(TokenType::Eq) {
+            let equals = self.previous().unwrap().clone();
+            let value = self.assignment()?;
+
+            match expr {
+                Expr::Variable { id, name } => Ok(Expr::Assign { id, name, value: value.into() }),
+                Expr::Get { object, name } => Ok(Expr::Set { object, name, value: value.into() }),
+                Expr::Index { object, bracket, index } => Ok(Expr::IndexSet { object, bracket, index, value: value.into() }),
+                _ => {
+                    self.error(&equals, "Invalid assignment target.");
+                    Ok(expr)
+                },
+            }
+        } else if
+            self.match_(TokenType::PlusEq) ||
+            self.match_(TokenType::MinusEq) ||
+            self.match_(TokenType::StarEq) ||
+            self.match_(TokenType::SlashEq)
+        {
+            let operator = self.previous().unwrap().clone();
+            let value = self.assignment()?;
+            Ok(self.update(expr, operator, value, false))
+        } else {
+            Ok(expr)
+        }
+    }
+
+    // Lowers `+=`, `++` and friends to an `Update` of an assignable target.
+    fn update(&mut self, target: Expr, operator: Token, value: Expr, postfix: bool) -> Expr {
+        if !matches!(target, Expr::Variable { .. } | Expr::Get { .. } | Expr::Index { .. }) {
+            self.error(&operator, "Invalid assignment target.");
+            return target;
+        }
+
+        let token_type = match operator.token_type {
+            TokenType::PlusEq | TokenType::PlusPlus => TokenType::Plus,
+            TokenType::MinusEq | TokenType::MinusMinus => TokenType::Minus,
+            TokenType::StarEq => TokenType::Star,
+            _ => TokenType::Slash,
+        };
+
+        Expr::Update {
+            target: target.into(),
+            operator: Token::new(token_type, operator.lexeme, operator.line),
+            value: value.into(),
+            postfix,
+        }
+    }
+
+    // The middle operand can be any expression, since the ':' delimits it;
+    // the last one is another conditional, so they nest to the right.
+    fn conditional(&mut self) -> Result<Expr> {
+        let condition = self.or()?;
+
+        if self.match_(TokenType::Question) {
+            let then_branch = self.expression()?;
+            self.consume(TokenType::Colon, "Expected ':' after then branch of conditional expression.")?;
+            let else_branch = self.conditional()?;
+            return Ok(Expr::Conditional {
+                condition: condition.into(),
+                then_branch: then_branch.into(),
+                else_branch: else_branch.into(),
+            });
+        }
+
+        Ok(condition)
+    }
+
+    fn or(&mut self) -> Result<Expr> {
+        let mut expr = self.and()?;
+
+        while self.match_(TokenType::Or) {
+            let operator = self.previous().unwrap().clone();
+            let right = self.and()?;
+            expr = Expr::Logical { left: expr.into(), operator, right: right.into() }
+        }
+
+        Ok(expr)
+    }
+
+    fn and(&mut self) -> Result<Expr> {
+        let mut expr = self.equality()?;
+
+        while self.match_(TokenType::And) {
+            let operator = self.previous().unwrap().clone();
+            let right = self.equality()?;
+            expr = Expr::Logical { left: expr.into(), operator, right: right.into() }
+        }
+
+        Ok(expr)
+    }
+
+    fn equality(&mut self) -> Result<Expr> {
+        let mut expr = self.comparision()?;
 
         while self.match_(TokenType::BangEq) || self.match_(TokenType::EqEq) {
             let operator = self.previous().unwrap().clone();
-            let right = self.comparision();
+            let right = self.comparision()?;
             expr = Expr::Binary { left: expr.into(), operator, right: right.into() }
         };
 
-        expr
+        Ok(expr)
     }
 
-    fn comparision(&mut self) -> Expr {
-        let mut expr = self.term();
+    fn comparision(&mut self) -> Result<Expr> {
+        let mut expr = self.bit_or()?;
 
         while
             self.match_(TokenType::Greater) ||
@@ -42,72 +533,318 @@ impl Parser {
             self.match_(TokenType::LessEq)
         {
             let operator = self.previous().unwrap().clone();
-            let right = self.term();
+            let right = self.bit_or()?;
             expr = Expr::Binary { left: expr.into(), operator, right: right.into() }
         };
 
-        expr
+        Ok(expr)
+    }
+
+    // The bitwise operators sit between comparison and arithmetic, as in
+    // Python, so `x & mask == 0` compares the masked value.
+    fn bit_or(&mut self) -> Result<Expr> {
+        let mut expr = self.bit_xor()?;
+
+        while self.match_(TokenType::Pipe) {
+            let operator = self.previous().unwrap().clone();
+            let right = self.bit_xor()?;
+            expr = Expr::Binary { left: expr.into(), operator, right: right.into() }
+        }
+
+        Ok(expr)
+    }
+
+    fn bit_xor(&mut self) -> Result<Expr> {
+        let mut expr = self.bit_and()?;
+
+        while self.match_(TokenType::Caret) {
+            let operator = self.previous().unwrap().clone();
+            let right = self.bit_and()?;
+            expr = Expr::Binary { left: expr.into(), operator, right: right.into() }
+        }
+
+        Ok(expr)
+    }
+
+    fn bit_and(&mut self) -> Result<Expr> {
+        let mut expr = self.shift()?;
+
+        while self.match_(TokenType::Ampersand) {
+            let operator = self.previous().unwrap().clone();
+            let right = self.shift()?;
+            expr = Expr::Binary { left: expr.into(), operator, right: right.into() }
+        }
+
+        Ok(expr)
     }
 
-    fn term(&mut self) -> Expr {
-        let mut expr = self.factor();
+    fn shift(&mut self) -> Result<Expr> {
+        let mut expr = self.term()?;
+
+        while self.match_(TokenType::LessLess) || self.match_(TokenType::GreaterGreater) {
+            let operator = self.previous().unwrap().clone();
+            let right = self.term()?;
+            expr = Expr::Binary { left: expr.into(), operator, right: right.into() }
+        }
+
+        Ok(expr)
+    }
+
+    fn term(&mut self) -> Result<Expr> {
+        let mut expr = self.factor()?;
 
         while self.match_(TokenType::Minus) || self.match_(TokenType::Plus) {
             let operator = self.previous().unwrap().clone();
-            let right = self.factor();
+            let right = self.factor()?;
             expr = Expr::Binary { left: expr.into(), operator, right: right.into() }
         }
 
-        expr
+        Ok(expr)
     }
 
-    fn factor(&mut self) -> Expr {
-        let mut expr = self.unary();
+    fn factor(&mut self) -> Result<Expr> {
+        let mut expr = self.unary()?;
 
-        while self.match_(TokenType::Slash) || self.match_(TokenType::Star) {
+        while
+            self.match_(TokenType::Slash) ||
+            self.match_(TokenType::Star) ||
+            self.match_(TokenType::Percent) ||
+            self.match_(TokenType::TildeSlash)
+        {
             let operator = self.previous().unwrap().clone();
-            let right = self.unary();
+            let right = self.unary()?;
             expr = Expr::Binary { left: expr.into(), operator, right: right.into() }
         }
 
-        expr
+        Ok(expr)
     }
 
-    fn unary(&mut self) -> Expr {
-        if self.match_(TokenType::Bang) || self.match_(TokenType::Minus) {
+    fn unary(&mut self) -> Result<Expr> {
+        if self.match_(TokenType::Bang) || self.match_(TokenType::Minus) || self.match_(TokenType::Tilde) {
+            let operator = self.previous().unwrap().clone();
+            let right = self.unary()?;
+            Ok(Expr::Unary { operator, right: right.into() })
+        } else if self.match_(TokenType::PlusPlus) || self.match_(TokenType::MinusMinus) {
             let operator = self.previous().unwrap().clone();
-            let right = self.unary();
-            Expr::Unary { operator, right: right.into() }
+            let target = self.unary()?;
+            Ok(self.update(target, operator, Expr::Literal { value: LiteralKind::Integer(1) }, false))
         } else {
-            self.primary()
+            self.power()
+        }
+    }
+
+    // `**` binds tighter than a unary operator on its left, so `-2 ** 2` is
+    // -4, but its right operand may itself be unary, as in `2 ** -1`. Going
+    // back through `unary` also makes it right-associative.
+    fn power(&mut self) -> Result<Expr> {
+        let expr = self.postfix()?;
+
+        if self.match_(TokenType::StarStar) {
+            let operator = self.previous().unwrap().clone();
+            let right = self.unary()?;
+            return Ok(Expr::Binary { left: expr.into(), operator, right: right.into() });
+        }
+
+        Ok(expr)
+    }
+
+    fn postfix(&mut self) -> Result<Expr> {
+        let expr = self.call()?;
+
+        if self.match_(TokenType::PlusPlus) || self.match_(TokenType::MinusMinus) {
+            let operator = self.previous().unwrap().clone();
+            return Ok(self.update(expr, operator, Expr::Literal { value: LiteralKind::Integer(1) }, true));
+        }
+
+        Ok(expr)
+    }
+
+    fn call(&mut self) -> Result<Expr> {
+        let mut expr = self.primary()?;
+
+        loop {
+            if self.match_(TokenType::LeftParen) {
+                expr = self.finish_call(expr)?;
+            } else if self.match_(TokenType::Dot) {
+                let name = self.consume_identifier("Expected property name after '.'.")?;
+                expr = Expr::Get { object: expr.into(), name };
+            } else if self.match_(TokenType::LeftBracket) {
+                let bracket = self.previous().unwrap().clone();
+                let index = self.expression()?;
+                self.consume(TokenType::RightBracket, "Expected ']' after index.")?;
+                expr = Expr::Index { object: expr.into(), bracket, index: index.into() };
+            } else {
+                break;
+            }
+        }
+
+        Ok(expr)
+    }
+
+    fn finish_call(&mut self, callee: Expr) -> Result<Expr> {
+        let mut arguments = Vec::new();
+        let mut named: Vec<(Token, Expr)> = Vec::new();
+        if !self.check(TokenType::RightParen) {
+            loop {
+                if self.check_identifier() && self.check_next(TokenType::Colon) {
+                    let name = self.advance().unwrap().clone();
+                    self.advance();
+                    if named.iter().any(|(other, _)| other.lexeme == name.lexeme) {
+                        self.error(&name, "Duplicate named argument.");
+                    }
+                    named.push((name, self.expression()?));
+                } else {
+                    let argument = self.expression()?;
+                    if let Some((name, _)) = named.last() {
+                        let name = name.clone();
+                        self.error(&name, "Positional arguments must come before named ones.");
+                    }
+                    arguments.push(argument);
+                }
+
+                if !self.match_(TokenType::Comma) {
+                    break;
+                }
+            }
         }
+
+        let paren = self.consume(TokenType::RightParen, "Expected ')' after arguments.")?;
+        Ok(Expr::Call { callee: callee.into(), paren, arguments, named })
     }
 
-    fn consume(&mut self, token_type: TokenType, message: &str) -> Option<&Token> {
+    fn consume(&mut self, token_type: TokenType, message: &str) -> Result<Token> {
         if self.check(token_type) {
-           self.advance()
+            Ok(self.advance().unwrap().clone())
         } else {
-            panic!("{}", message);
+            Err(ParseError::new(self.peek().unwrap(), message))
         }
     }
 
-    fn primary(&mut self) -> Expr {
-        if let Some(token) = self.advance() {
-           match &token.token_type {
-               TokenType::True => Expr::Literal { value: LiteralKind::Bool(true) },
-               TokenType::False => Expr::Literal { value: LiteralKind::Bool(false) },
-               TokenType::Nil => Expr::Literal { value: LiteralKind::Nil },
-               TokenType::Float(f) => Expr::Literal { value: LiteralKind::Float(f.clone()) },
-               TokenType::String(s) => Expr::Literal { value: LiteralKind::String(s.clone()) },
-               TokenType::LeftParen => {
-                   let expr = self.expression();
-                   self.consume(TokenType::RightParen, "Expected ')' after expression.");
-                   Expr::Grouping { expr: expr.into() }
-               },
-               _ => todo!(),
-           }
+    fn consume_identifier(&mut self, message: &str) -> Result<Token> {
+        if self.check_identifier() {
+            Ok(self.advance().unwrap().clone())
         } else {
-            panic!("Couldn't parse primary at end of stream");
+            Err(ParseError::new(self.peek().unwrap(), message))
+        }
+    }
+
+    fn primary(&mut self) -> Result<Expr> {
+        if self.is_at_end() {
+            return Err(ParseError::new(self.peek().unwrap(), "Expected expression."));
+        }
+
+        let token = self.advance().unwrap().clone();
+        let expr = match &token.token_type {
+            TokenType::True => Expr::Literal { value: LiteralKind::Bool(true) },
+            TokenType::False => Expr::Literal { value: LiteralKind::Bool(false) },
+            TokenType::Nil => Expr::Literal { value: LiteralKind::Nil },
+            TokenType::Integer(i) => Expr::Literal { value: LiteralKind::Integer(*i) },
+            TokenType::BigInteger(i) => Expr::Literal { value: LiteralKind::BigInteger(i.clone()) },
+            TokenType::Float(f) => Expr::Literal { value: LiteralKind::Float(*f) },
+            TokenType::String(s) => Expr::Literal { value: LiteralKind::String(s.clone()) },
+            TokenType::Interpolation(s) => {
+                let mut parts = vec![Expr::Literal { value: LiteralKind::String(s.clone()) }];
+                loop {
+                    parts.push(self.expression()?);
+
+                    let text = self.advance().unwrap().clone();
+                    match text.token_type {
+                        TokenType::Interpolation(s) => parts.push(Expr::Literal { value: LiteralKind::String(s) }),
+                        TokenType::InterpolationEnd(s) => {
+                            parts.push(Expr::Literal { value: LiteralKind::String(s) });
+                            break;
+                        },
+                        _ => return Err(ParseError::new(&text, "Expected '}' after interpolated expression.")),
+                    }
+                }
+
+                Expr::Interpolation { parts }
+            },
+            TokenType::Super => {
+                self.consume(TokenType::Dot, "Expected '.' after 'super'.")?;
+                let method = self.consume_identifier("Expected superclass method name.")?;
+                Expr::Super { id: self.next_id(), keyword: token, method }
+            },
+            TokenType::This => Expr::This { id: self.next_id(), keyword: token },
+            TokenType::Identifier(_) => Expr::Variable { id: self.next_id(), name: token },
+            TokenType::LeftBracket => {
+                let mut elements = Vec::new();
+                if !self.check(TokenType::RightBracket) {
+                    loop {
+                        elements.push(self.expression()?);
+                        if !self.match_(TokenType::Comma) {
+                            break;
+                        }
+                    }
+                }
+
+                self.consume(TokenType::RightBracket, "Expected ']' after list elements.")?;
+                Expr::List { elements }
+            },
+            TokenType::LeftBrace => {
+                let mut entries = Vec::new();
+                if !self.check(TokenType::RightBrace) {
+                    loop {
+                        let key = self.expression()?;
+                        self.consume(TokenType::Colon, "Expected ':' after map key.")?;
+                        let value = self.expression()?;
+                        entries.push((key, value));
+                        if !self.match_(TokenType::Comma) {
+                            break;
+                        }
+                    }
+                }
+
+                self.consume(TokenType::RightBrace, "Expected '}' after map entries.")?;
+                Expr::Map { brace: token, entries }
+            },
+            TokenType::Fun => self.lambda(&token)?,
+            TokenType::LeftParen if self.check_arrow_lambda() => self.arrow_lambda()?,
+            TokenType::LeftParen => {
+                let expr = self.expression()?;
+                self.consume(TokenType::RightParen, "Expected ')' after expression.")?;
+                Expr::Grouping { expr: expr.into() }
+            },
+            _ => return Err(ParseError::new(&token, "Expected expression.")),
+        };
+
+        Ok(expr)
+    }
+
+    fn next_id(&mut self) -> ExprId {
+        self.next_id += 1;
+        self.next_id
+    }
+
+    // Records an error that doesn't leave the parser in a confused state, so
+    // parsing can carry on without synchronizing.
+    fn error(&mut self, token: &Token, message: &str) {
+        self.errors.push(ParseError::new(token, message));
+    }
+
+    fn synchronize(&mut self) {
+        self.advance();
+
+        while !self.is_at_end() {
+            if self.previous().unwrap().token_type == TokenType::Semicolon {
+                return;
+            }
+
+            match self.peek().unwrap().token_type {
+                TokenType::Class
+                | TokenType::Fun
+                | TokenType::Var
+                | TokenType::For
+                | TokenType::If
+                | TokenType::While
+                | TokenType::Print
+                | TokenType::Return
+                | TokenType::Throw
+                | TokenType::Try
+                | TokenType::Break
+                | TokenType::Continue => return,
+                _ => { self.advance(); },
+            }
         }
     }
 
@@ -128,12 +865,69 @@ impl Parser {
         }
     }
 
+    fn check_identifier(&self) -> bool {
+        matches!(self.peek(), Some(Token { token_type: TokenType::Identifier(_), .. }))
+    }
+
+    fn check_next_identifier(&self) -> bool {
+        matches!(self.tokens.get(self.current as usize + 1), Some(Token { token_type: TokenType::Identifier(_), .. }))
+    }
+
+    // Called just after a '(' in expression position, which starts an arrow
+    // lambda's parameters rather than a grouping if the matching ')' is
+    // followed by '=>'.
+    fn check_arrow_lambda(&self) -> bool {
+        let mut depth = 1;
+        for (position, token) in self.tokens.iter().enumerate().skip(self.current as usize) {
+            match token.token_type {
+                TokenType::LeftParen => depth += 1,
+                TokenType::RightParen if depth == 1 => {
+                    return self.tokens.get(position + 1).is_some_and(|next| next.token_type == TokenType::Arrow);
+                },
+                TokenType::RightParen => depth -= 1,
+                _ => {},
+            }
+        }
+
+        false
+    }
+
+    fn check_next(&self, token_type: TokenType) -> bool {
+        match self.tokens.get(self.current as usize + 1) {
+            Some(next) => next.token_type == token_type,
+            None => false,
+        }
+    }
+
+    // A '{' in expression position is always a map, but at the start of a
+    // statement it opens a block unless it's followed by a literal key and a
+    // ':', which no block could start with.
+    fn check_map_literal(&self) -> bool {
+        let key = self.tokens.get(self.current as usize + 1).map(|token| &token.token_type);
+        let is_literal_key = matches!(
+            key,
+            Some(TokenType::String(_) | TokenType::Integer(_) | TokenType::BigInteger(_) | TokenType::Float(_) | TokenType::True | TokenType::False)
+        );
+
+        is_literal_key && self.tokens.get(self.current as usize + 2)
+            .is_some_and(|token| token.token_type == TokenType::Colon)
+    }
+
+    fn is_at_end(&self) -> bool {
+        match self.peek() {
+            Some(token) => token.token_type == TokenType::EOF,
+            None => true,
+        }
+    }
+
     fn peek(&self) -> Option<&Token> {
         self.tokens.get(self.current as usize)
     }
 
     fn advance(&mut self) -> Option<&Token> {
-        self.current = self.current.saturating_add(1);
+        if !self.is_at_end() {
+            self.current = self.current.saturating_add(1);
+        }
         self.previous()
     }
 
@@ -152,3 +946,8 @@ impl Display for Parser {
         write!(f, "{buffer}")
     }
 }
+
+// Lambdas have no name of their own, so they print as `<fn lambda>`.
+fn lambda_name(token: &Token) -> Token {
+    Token::new(TokenType::Identifier("lambda".into()), "lambda".into(), token.line)
+}
@@ -0,0 +1,323 @@
+use std::{collections::HashMap, fmt::Display};
+
+use crate::{ast::{Expr, ExprId, Function, Stmt}, token::Token};
+
+#[derive(Debug)]
+pub struct ResolveError {
+    pub line: usize,
+    pub lexeme: String,
+    pub message: String,
+}
+
+impl ResolveError {
+    fn new(token: &Token, message: &str) -> ResolveError {
+        ResolveError { line: token.line, lexeme: token.lexeme.clone(), message: message.into() }
+    }
+}
+
+impl Display for ResolveError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "[line {}] Error at '{}': {}", self.line, self.lexeme, self.message)
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Default)]
+enum FunctionType {
+    #[default]
+    None,
+    Function,
+    Initializer,
+    Method,
+}
+
+#[derive(Clone, Copy, PartialEq, Default)]
+enum ClassType {
+    #[default]
+    None,
+    Class,
+    Subclass,
+}
+
+// Walks the AST once before it runs, recording for every local variable
+// reference how many scopes separate it from its declaration. Globals are
+// left out of the table and looked up dynamically.
+#[derive(Default)]
+pub struct Resolver {
+    // Each scope maps a name to whether its initializer has finished.
+    scopes: Vec<HashMap<String, bool>>,
+    locals: HashMap<ExprId, usize>,
+    current_function: FunctionType,
+    current_class: ClassType,
+    errors: Vec<ResolveError>,
+}
+
+impl Resolver {
+    pub fn new() -> Resolver {
+        Resolver::default()
+    }
+
+    pub fn resolve(mut self, statements: &[Stmt]) -> Result<HashMap<ExprId, usize>, Vec<ResolveError>> {
+        self.resolve_statements(statements);
+
+        if self.errors.is_empty() {
+            Ok(self.locals)
+        } else {
+            Err(self.errors)
+        }
+    }
+
+    fn resolve_statements(&mut self, statements: &[Stmt]) {
+        for statement in statements {
+            self.resolve_statement(statement);
+        }
+    }
+
+    fn resolve_statement(&mut self, stmt: &Stmt) {
+        match stmt {
+            Stmt::Block { statements } => {
+                self.begin_scope();
+                self.resolve_statements(statements);
+                self.end_scope();
+            },
+            Stmt::Break { .. } | Stmt::Continue { .. } => {},
+            Stmt::Class { name, superclass, methods } => {
+                let enclosing_class = self.current_class;
+                self.current_class = ClassType::Class;
+
+                self.declare(name);
+                self.define(name);
+
+                if let Some(superclass) = superclass {
+                    if let Expr::Variable { name: superclass_name, .. } = superclass {
+                        if superclass_name.lexeme == name.lexeme {
+                            self.error(superclass_name, "A class can't inherit from itself.");
+                        }
+                    }
+
+                    self.current_class = ClassType::Subclass;
+                    self.resolve_expression(superclass);
+
+                    self.begin_scope();
+                    self.scopes.last_mut().unwrap().insert("super".into(), true);
+                }
+
+                self.begin_scope();
+                self.scopes.last_mut().unwrap().insert("this".into(), true);
+                for method in methods {
+                    let function_type = if method.name.lexeme == "init" {
+                        FunctionType::Initializer
+                    } else {
+                        FunctionType::Method
+                    };
+                    self.resolve_function(method, function_type);
+                }
+                self.end_scope();
+
+                if superclass.is_some() {
+                    self.end_scope();
+                }
+
+                self.current_class = enclosing_class;
+            },
+            Stmt::Expression { expr } | Stmt::Print { expr } => self.resolve_expression(expr),
+            Stmt::Function { function } => {
+                self.declare(&function.name);
+                self.define(&function.name);
+                self.resolve_function(function, FunctionType::Function);
+            },
+            Stmt::If { condition, then_branch, else_branch } => {
+                self.resolve_expression(condition);
+                self.resolve_statement(then_branch);
+                if let Some(else_branch) = else_branch {
+                    self.resolve_statement(else_branch);
+                }
+            },
+            Stmt::Return { keyword, value } => {
+                if self.current_function == FunctionType::None {
+                    self.error(keyword, "Can't return from top-level code.");
+                }
+
+                if let Some(value) = value {
+                    if self.current_function == FunctionType::Initializer {
+                        self.error(keyword, "Can't return a value from an initializer.");
+                    }
+                    self.resolve_expression(value);
+                }
+            },
+            Stmt::Throw { value, .. } => self.resolve_expression(value),
+            Stmt::Try { body, catch, finally } => {
+                self.begin_scope();
+                self.resolve_statements(body);
+                self.end_scope();
+
+                if let Some(catch) = catch {
+                    // The error variable shares a scope with the catch body.
+                    self.begin_scope();
+                    self.declare(&catch.name);
+                    self.define(&catch.name);
+                    self.resolve_statements(&catch.body);
+                    self.end_scope();
+                }
+
+                if let Some(finally) = finally {
+                    self.begin_scope();
+                    self.resolve_statements(finally);
+                    self.end_scope();
+                }
+            },
+            Stmt::Var { name, initializer } => {
+                self.declare(name);
+                if let Some(initializer) = initializer {
+                    self.resolve_expression(initializer);
+                }
+                self.define(name);
+            },
+            Stmt::While { condition, body, increment, .. } => {
+                self.resolve_expression(condition);
+                self.resolve_statement(body);
+                if let Some(increment) = increment {
+                    self.resolve_expression(increment);
+                }
+            },
+        }
+    }
+
+    fn resolve_expression(&mut self, expr: &Expr) {
+        match expr {
+            Expr::Assign { id, name, value } => {
+                self.resolve_expression(value);
+                self.resolve_local(*id, name);
+            },
+            Expr::Binary { left, right, .. } | Expr::Logical { left, right, .. } => {
+                self.resolve_expression(left);
+                self.resolve_expression(right);
+            },
+            Expr::Call { callee, arguments, named, .. } => {
+                self.resolve_expression(callee);
+                for argument in arguments.iter().chain(named.iter().map(|(_, value)| value)) {
+                    self.resolve_expression(argument);
+                }
+            },
+            Expr::Conditional { condition, then_branch, else_branch } => {
+                self.resolve_expression(condition);
+                self.resolve_expression(then_branch);
+                self.resolve_expression(else_branch);
+            },
+            Expr::Get { object, .. } => self.resolve_expression(object),
+            Expr::Grouping { expr } => self.resolve_expression(expr),
+            Expr::Index { object, index, .. } => {
+                self.resolve_expression(object);
+                self.resolve_expression(index);
+            },
+            Expr::IndexSet { object, index, value, .. } => {
+                self.resolve_expression(object);
+                self.resolve_expression(index);
+                self.resolve_expression(value);
+            },
+            Expr::Interpolation { parts: elements } | Expr::List { elements } => {
+                for element in elements {
+                    self.resolve_expression(element);
+                }
+            },
+            Expr::Lambda { function } => self.resolve_function(function, FunctionType::Function),
+            Expr::Literal { .. } => {},
+            Expr::Map { entries, .. } => {
+                for (key, value) in entries {
+                    self.resolve_expression(key);
+                    self.resolve_expression(value);
+                }
+            },
+            Expr::Set { object, value, .. } => {
+                self.resolve_expression(value);
+                self.resolve_expression(object);
+            },
+            Expr::Super { id, keyword, .. } => {
+                match self.current_class {
+                    ClassType::None => self.error(keyword, "Can't use 'super' outside of a class."),
+                    ClassType::Class => self.error(keyword, "Can't use 'super' in a class with no superclass."),
+                    ClassType::Subclass => {},
+                }
+
+                self.resolve_local(*id, keyword);
+            },
+            Expr::This { id, keyword } => {
+                if self.current_class == ClassType::None {
+                    self.error(keyword, "Can't use 'this' outside of a class.");
+                }
+
+                self.resolve_local(*id, keyword);
+            },
+            Expr::Unary { right, .. } => self.resolve_expression(right),
+            Expr::Update { target, value, .. } => {
+                self.resolve_expression(value);
+                self.resolve_expression(target);
+            },
+            Expr::Variable { id, name } => {
+                if let Some(false) = self.scopes.last().and_then(|scope| scope.get(&name.lexeme)) {
+                    self.error(name, "Can't read local variable in its own initializer.");
+                }
+
+                self.resolve_local(*id, name);
+            },
+        }
+    }
+
+    fn resolve_function(&mut self, function: &Function, function_type: FunctionType) {
+        let enclosing_function = self.current_function;
+        self.current_function = function_type;
+
+        self.begin_scope();
+        // Each default sees the parameters before it.
+        for param in &function.params {
+            if let Some(default) = &param.default {
+                self.resolve_expression(default);
+            }
+            self.declare(&param.name);
+            self.define(&param.name);
+        }
+        if let Some(rest) = &function.rest {
+            self.declare(rest);
+            self.define(rest);
+        }
+        self.resolve_statements(&function.body);
+        self.end_scope();
+
+        self.current_function = enclosing_function;
+    }
+
+    fn resolve_local(&mut self, id: ExprId, name: &Token) {
+        let depth = self.scopes.iter()
+            .rev()
+            .position(|scope| scope.contains_key(&name.lexeme));
+
+        if let Some(depth) = depth {
+            self.locals.insert(id, depth);
+        }
+    }
+
+    fn begin_scope(&mut self) {
+        self.scopes.push(HashMap::new());
+    }
+
+    fn end_scope(&mut self) {
+        self.scopes.pop();
+    }
+
+    fn declare(&mut self, name: &Token) {
+        let Some(scope) = self.scopes.last_mut() else { return };
+
+        if scope.insert(name.lexeme.clone(), false).is_some() {
+            self.error(name, "Already a variable with this name in this scope.");
+        }
+    }
+
+    fn define(&mut self, name: &Token) {
+        if let Some(scope) = self.scopes.last_mut() {
+            scope.insert(name.lexeme.clone(), true);
+        }
+    }
+
+    fn error(&mut self, token: &Token, message: &str) {
+        self.errors.push(ResolveError::new(token, message));
+    }
+}
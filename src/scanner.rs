@@ -1,28 +1,50 @@
 use std::fmt::Display;
 
-use crate::{token::Token, token_type::TokenType};
+use crate::{bigint::BigInt, token::Token, token_type::TokenType};
+
+#[derive(Debug)]
+pub struct ScanError {
+    pub line: usize,
+    pub column: usize,
+    pub message: String,
+}
+
+impl Display for ScanError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "[line {}, column {}] Error: {}", self.line, self.column, self.message)
+    }
+}
 
 pub struct Scanner {
-    source: String,
+    source: Vec<char>,
     pub tokens: Vec<Token>,
+    errors: Vec<ScanError>,
+    // One entry per `${` we're inside, counting the unmatched `{`s opened
+    // since, so we know which `}` ends the embedded expression.
+    interpolations: Vec<usize>,
 
     start: usize,
     current: usize,
     line: usize,
+    // Index of the first character on the current line, for error columns.
+    line_start: usize,
 }
 
 impl Scanner {
     pub fn new(source: String) -> Scanner {
         Scanner { 
-            source, 
+            source: source.chars().collect(), 
             tokens: Vec::new(), 
+            errors: Vec::new(),
+            interpolations: Vec::new(),
             current: 0,
             start: 0,
             line: 1,
+            line_start: 0,
         }
     }
 
-    pub fn scan_tokens(&mut self) {
+    pub fn scan_tokens(&mut self) -> Result<(), Vec<ScanError>> {
         while !self.is_at_end() {
             self.start = self.current;
             let token = self.scan_token();
@@ -31,60 +53,168 @@ impl Scanner {
             }
         }
 
-        self.tokens.push(Token::new(TokenType::EOF, self.line));
+        if !self.interpolations.is_empty() {
+            self.error(self.current, "Unterminated string interpolation.".into());
+        }
+
+        self.tokens.push(Token::new(TokenType::EOF, String::new(), self.line));
+
+        if self.errors.is_empty() {
+            Ok(())
+        } else {
+            Err(std::mem::take(&mut self.errors))
+        }
+    }
+
+    // Reports an error at the character with the given index.
+    fn error(&mut self, position: usize, message: String) {
+        let column = position - self.line_start + 1;
+        self.errors.push(ScanError { line: self.line, column, message });
+    }
+
+    fn newline(&mut self) {
+        self.line = self.line.saturating_add(1);
+        self.line_start = self.current;
     }
 
     fn is_at_end(&self) -> bool {
         self.current >= self.source.len()
     }
     
-    fn parse_string(&mut self) -> String {
+    // Scans string contents up to the closing quote, or up to a `${` that
+    // starts an embedded expression. In the latter case the text so far
+    // becomes an `Interpolation` token, the expression is scanned as normal
+    // tokens, and the `}` that closes it resumes the string. The text after
+    // the last expression becomes an `InterpolationEnd` token.
+    fn parse_string(&mut self, resumed: bool) -> Option<TokenType> {
         let mut string = String::new();
+        let (line, column) = (self.line, self.start - self.line_start + 1);
 
-        // Opening "
+        // Opening " or the } that closed an interpolation
         self.advance();
         while self.peek() != '"' && !self.is_at_end() {
-            if self.peek() == '\n' {
-                self.line = self.line.saturating_add(1);
-            }
-
             let c = self.advance();
-            string.push(c);
+            match c {
+                '\\' => {
+                    if let Some(escaped) = self.parse_escape() {
+                        string.push(escaped);
+                    }
+                },
+                '$' if self.peek() == '{' => {
+                    self.advance();
+                    self.interpolations.push(0);
+                    return Some(TokenType::Interpolation(string));
+                },
+                '\n' => {
+                    self.newline();
+                    string.push(c);
+                },
+                _ => string.push(c),
+            }
         }
 
         if self.is_at_end() {
-            panic!("Unterminated string literal")
+            self.errors.push(ScanError { line, column, message: "Unterminated string.".into() });
+            return None;
         }
 
         // Closing "
         self.advance();
 
-        string
+        if resumed {
+            Some(TokenType::InterpolationEnd(string))
+        } else {
+            Some(TokenType::String(string))
+        }
+    }
+
+    // Called just after the backslash of an escape sequence has been consumed.
+    fn parse_escape(&mut self) -> Option<char> {
+        let backslash = self.current - 1;
+        if self.is_at_end() {
+            return None;
+        }
+
+        let escaped = match self.advance() {
+            '"' => '"',
+            '\\' => '\\',
+            '$' => '$',
+            'n' => '\n',
+            't' => '\t',
+            'r' => '\r',
+            '0' => '\0',
+            'u' => return self.parse_unicode_escape(backslash),
+            c => {
+                self.error(backslash, format!("Invalid escape sequence '\\{c}'."));
+                return None;
+            },
+        };
+
+        Some(escaped)
+    }
+
+    // Parses the `{1F600}` part of a `\u{1F600}` escape.
+    fn parse_unicode_escape(&mut self, backslash: usize) -> Option<char> {
+        if !self.match_('{') {
+            self.error(backslash, "Expected '{' after '\\u'.".into());
+            return None;
+        }
+
+        let mut digits = String::new();
+        while self.peek().is_ascii_hexdigit() {
+            digits.push(self.advance());
+        }
+
+        if !self.match_('}') {
+            self.error(backslash, "Expected '}' to close Unicode escape.".into());
+            return None;
+        }
+
+        if digits.is_empty() || digits.len() > 6 {
+            self.error(backslash, "Unicode escape must have between 1 and 6 hex digits.".into());
+            return None;
+        }
+
+        let code = u32::from_str_radix(&digits, 16).ok()?;
+        let c = char::from_u32(code);
+        if c.is_none() {
+            self.error(backslash, format!("'{digits}' is not a valid Unicode code point."));
+        }
+
+        c
     }
 
-    fn parse_number(&mut self) -> f32 {
+    // Numbers with a fractional part are floats; everything else is an
+    // integer, big or otherwise.
+    fn parse_number(&mut self) -> Option<TokenType> {
         let mut num = String::new();
 
-        while self.peek().is_digit(10) {
+        while self.peek().is_ascii_digit() {
             num.push(self.advance());
         };
 
-        if self.peek() == '.' && self.peek_next().is_digit(10) {
+        if self.peek() == '.' && self.peek_next().is_ascii_digit() {
             // Consume "."
             num.push(self.advance());
 
-            while self.peek().is_digit(10) {
+            while self.peek().is_ascii_digit() {
                 num.push(self.advance());
             };
+
+            // Digits with a single "." always parse
+            return Some(TokenType::Float(num.parse().unwrap()));
         };
 
-        // Ideally this should never panic
-        num.parse().unwrap()
+        // Only digits here, so a failed parse means the literal overflowed.
+        match num.parse() {
+            Ok(integer) => Some(TokenType::Integer(integer)),
+            Err(_) => BigInt::parse(&num).map(TokenType::BigInteger),
+        }
     }
 
     fn parse_identifier(&mut self) -> TokenType {
         let mut identifier = String::new();
-        while self.peek().is_alphanumeric() && !self.is_at_end() {
+        while (self.peek().is_alphanumeric() || self.peek() == '_') && !self.is_at_end() {
             let c = self.advance();
             identifier.push(c);
         };
@@ -107,27 +237,69 @@ impl Scanner {
             },
             '{' => {
                 self.advance();
+                if let Some(depth) = self.interpolations.last_mut() {
+                    *depth += 1;
+                }
                 Some(TokenType::LeftBrace)
             },
+            '}' if self.interpolations.last() == Some(&0) => {
+                self.interpolations.pop();
+                self.parse_string(true)
+            },
             '}' => {
                 self.advance();
+                if let Some(depth) = self.interpolations.last_mut() {
+                    *depth -= 1;
+                }
                 Some(TokenType::RightBrace)
             },
+            '[' => {
+                self.advance();
+                Some(TokenType::LeftBracket)
+            },
+            ']' => {
+                self.advance();
+                Some(TokenType::RightBracket)
+            },
+            ':' => {
+                self.advance();
+                Some(TokenType::Colon)
+            },
+            '?' => {
+                self.advance();
+                Some(TokenType::Question)
+            },
             ',' => {
                 self.advance();
                 Some(TokenType::Comma)
             },
+            '.' if self.peek_next() == '.' && self.source.get(self.current + 2) == Some(&'.') => {
+                self.current += 3;
+                Some(TokenType::Ellipsis)
+            },
             '.' => {
                 self.advance();
                 Some(TokenType::Dot)
             },
             '-' => {
                 self.advance();
-                Some(TokenType::Minus)
+                Some(if self.match_('=') {
+                    TokenType::MinusEq
+                } else if self.match_('-') {
+                    TokenType::MinusMinus
+                } else {
+                    TokenType::Minus
+                })
             },
             '+' => {
                 self.advance();
-                Some(TokenType::Plus)
+                Some(if self.match_('=') {
+                    TokenType::PlusEq
+                } else if self.match_('+') {
+                    TokenType::PlusPlus
+                } else {
+                    TokenType::Plus
+                })
             },
             ';' => {
                 self.advance();
@@ -135,11 +307,37 @@ impl Scanner {
             },
             '*' => {
                 self.advance();
-                Some(TokenType::Star)
+                Some(if self.match_('*') {
+                    TokenType::StarStar
+                } else if self.match_('=') {
+                    TokenType::StarEq
+                } else {
+                    TokenType::Star
+                })
+            },
+            '%' => {
+                self.advance();
+                Some(TokenType::Percent)
+            },
+            '~' => {
+                self.advance();
+                Some(if self.match_('/') { TokenType::TildeSlash } else { TokenType::Tilde })
+            },
+            '&' => {
+                self.advance();
+                Some(TokenType::Ampersand)
+            },
+            '|' => {
+                self.advance();
+                Some(TokenType::Pipe)
+            },
+            '^' => {
+                self.advance();
+                Some(TokenType::Caret)
             },
             '\n' => { 
-                self.line = self.line.saturating_add(1);
                 self.advance();
+                self.newline();
                 None
             },
             ' ' | '\r' | '\t' => {
@@ -152,15 +350,33 @@ impl Scanner {
             }
             '=' => {
                 self.advance();
-                Some(if self.match_('=') { TokenType::EqEq } else { TokenType::Eq })
+                Some(if self.match_('=') {
+                    TokenType::EqEq
+                } else if self.match_('>') {
+                    TokenType::Arrow
+                } else {
+                    TokenType::Eq
+                })
             },
             '<' => {
                 self.advance();
-                Some(if self.match_('=') { TokenType::LessEq } else { TokenType::Less})
+                Some(if self.match_('=') {
+                    TokenType::LessEq
+                } else if self.match_('<') {
+                    TokenType::LessLess
+                } else {
+                    TokenType::Less
+                })
             },
             '>' => {
                 self.advance();
-                Some(if self.match_('=') { TokenType::GreaterEq } else { TokenType::Greater })
+                Some(if self.match_('=') {
+                    TokenType::GreaterEq
+                } else if self.match_('>') {
+                    TokenType::GreaterGreater
+                } else {
+                    TokenType::Greater
+                })
             },
             '/' => {
                 self.advance();
@@ -171,24 +387,27 @@ impl Scanner {
                     }
 
                     None
+                } else if self.match_('=') {
+                    Some(TokenType::SlashEq)
                 } else {
                     Some(TokenType::Slash)
                 }
             }
-            '"' => Some(TokenType::String(self.parse_string())),
-            '0'..='9' => Some(TokenType::Float(self.parse_number())),
+            '"' => self.parse_string(false),
+            '0'..='9' => self.parse_number(),
             'a'..='z' | 'A'..='Z' | '_' => Some(self.parse_identifier()),
-            _ => panic!("Unrecognized token: {} on line {}", c, self.line),
+            _ => {
+                self.advance();
+                self.error(self.start, format!("Unexpected character '{c}'."));
+                None
+            },
         };
 
-        match token_type {
-            Some(t) => Some(Token::new(t, self.line)),
-            None => None,
-        }
+        token_type.map(|t| Token::new(t, self.source[self.start..self.current].iter().collect(), self.line))
     }
 
     fn _char_at(&self, index: usize) -> char {
-        self.source.as_bytes()[index] as char
+        self.source[index]
     }
 
     fn peek(&self) -> char {
@@ -233,12 +452,16 @@ impl  Display for Scanner {
     }
 }
 
-fn get_keyword(identifier: &String) -> Option<TokenType> {
-    match identifier.as_str() {
+fn get_keyword(identifier: &str) -> Option<TokenType> {
+    match identifier {
         "and" => Some(TokenType::And),
+        "break" => Some(TokenType::Break),
+        "catch" => Some(TokenType::Catch),
         "class" => Some(TokenType::Class),
+        "continue" => Some(TokenType::Continue),
         "else" => Some(TokenType::Else),
         "false" => Some(TokenType::False),
+        "finally" => Some(TokenType::Finally),
         "for" => Some(TokenType::For),
         "fun" => Some(TokenType::Fun),
         "if" => Some(TokenType::If),
@@ -248,7 +471,9 @@ fn get_keyword(identifier: &String) -> Option<TokenType> {
         "return" => Some(TokenType::Return),
         "super" => Some(TokenType::Super),
         "this" => Some(TokenType::This),
+        "throw" => Some(TokenType::Throw),
         "true" => Some(TokenType::True),
+        "try" => Some(TokenType::Try),
         "var" => Some(TokenType::Var),
         "while" => Some(TokenType::While),
         _ => None,
@@ -5,6 +5,7 @@ use crate::token_type::TokenType;
 #[derive(Clone)]
 pub struct Token {
     pub token_type: TokenType,
+    pub lexeme: String,
     pub line: usize,
 }
 
@@ -15,7 +16,7 @@ impl fmt::Display for Token {
 }
 
 impl Token {
-    pub fn new(token_type: TokenType, line: usize) -> Token {
-        Token { token_type, line, }
+    pub fn new(token_type: TokenType, lexeme: String, line: usize) -> Token {
+        Token { token_type, lexeme, line, }
     }
 }
@@ -1,5 +1,7 @@
 use std::fmt::Display;
 
+use crate::bigint::BigInt;
+
 #[derive(Debug, PartialEq, Clone)]
 pub enum TokenType {
     // Single-character tokens.
@@ -7,33 +9,66 @@ pub enum TokenType {
     RightParen, 
     LeftBrace, 
     RightBrace,
+    LeftBracket,
+    RightBracket,
+    Colon,
+    Question,
     Comma, 
     Dot, 
+    Ellipsis,
     Minus, 
     Plus, 
     Semicolon, 
     Slash, 
     Star,
+    Percent,
+    Ampersand,
+    Pipe,
+    Caret,
+    Tilde,
 
     Bang,
     BangEq,
     Eq,
     EqEq,
+    Arrow,
     Greater,
     GreaterEq,
     Less,
     LessEq,
+    LessLess,
+    GreaterGreater,
+    PlusEq,
+    MinusEq,
+    StarEq,
+    SlashEq,
+    PlusPlus,
+    MinusMinus,
+    StarStar,
+    // Floor division, spelled `~/` since `//` starts a comment.
+    TildeSlash,
 
     Identifier(String),
     String(String),
-    Float(f32),
+    // The text of a string literal before an embedded `${...}` expression.
+    Interpolation(String),
+    // The rest of a string literal after its last `${...}` expression.
+    InterpolationEnd(String),
+    Integer(i64),
+    // An integer literal too large for an i64.
+    BigInteger(BigInt),
+    Float(f64),
 
     And,
+    Break,
+    Catch,
     Class,
+    Continue,
     If,
     Else,
     True,
     False,
+    Finally,
     Fun,
     For,
     Nil,
@@ -42,6 +77,8 @@ pub enum TokenType {
     Return,
     Super,
     This,
+    Throw,
+    Try,
     Var,
     While,
 
@@ -53,15 +90,39 @@ impl Display for TokenType {
         let res = match self {
             TokenType::Identifier(identifier) => format!("Identifier('{}')", identifier),
             TokenType::String(string) => format!("String('{}')", string),
+            TokenType::Interpolation(string) => format!("Interpolation('{}')", string),
+            TokenType::InterpolationEnd(string) => format!("InterpolationEnd('{}')", string),
+            TokenType::Integer(integer) => format!("Integer({})", integer),
+            TokenType::BigInteger(integer) => format!("BigInteger({})", integer),
             TokenType::Float(float) => format!("Float({})", float),
             TokenType::Star => "*".into(),
+            TokenType::StarStar => "**".into(),
+            TokenType::Percent => "%".into(),
+            TokenType::TildeSlash => "~/".into(),
+            TokenType::Ampersand => "&".into(),
+            TokenType::Pipe => "|".into(),
+            TokenType::Caret => "^".into(),
+            TokenType::Tilde => "~".into(),
+            TokenType::LessLess => "<<".into(),
+            TokenType::PlusEq => "+=".into(),
+            TokenType::MinusEq => "-=".into(),
+            TokenType::StarEq => "*=".into(),
+            TokenType::SlashEq => "/=".into(),
+            TokenType::PlusPlus => "++".into(),
+            TokenType::MinusMinus => "--".into(),
+            TokenType::GreaterGreater => ">>".into(),
             TokenType::Minus => "-".into(),
             TokenType::LeftParen => "(".into(),
             TokenType::RightParen => ")".into(),
             TokenType::LeftBrace => "{".into(),
             TokenType::RightBrace => "}".into(),
+            TokenType::LeftBracket => "[".into(),
+            TokenType::RightBracket => "]".into(),
+            TokenType::Colon => ":".into(),
+            TokenType::Question => "?".into(),
             TokenType::Comma => ",".into(),
             TokenType::Dot => ".".into(),
+            TokenType::Ellipsis => "...".into(),
             TokenType::Plus => "+".into(),
             TokenType::Semicolon => ";".into(),
             TokenType::Slash => "/".into(),
@@ -69,24 +130,31 @@ impl Display for TokenType {
             TokenType::BangEq => "!=".into(),
             TokenType::Eq => "=".into(),
             TokenType::EqEq => "==".into(),
+            TokenType::Arrow => "=>".into(),
             TokenType::Greater => ">".into(),
             TokenType::GreaterEq => ">=".into(),
             TokenType::Less => "<".into(),
             TokenType::LessEq => "<=".into(),
-            TokenType::And => "&".into(),
+            TokenType::And => "AND".into(),
+            TokenType::Break => "BREAK".into(),
+            TokenType::Catch => "CATCH".into(),
             TokenType::Class => "CLASS".into(),
+            TokenType::Continue => "CONTINUE".into(),
             TokenType::If => "IF".into(),
             TokenType::Else => "ELSE".into(),
             TokenType::True => "TRUE".into(),
             TokenType::False => "FALSE".into(),
+            TokenType::Finally => "FINALLY".into(),
             TokenType::Fun => "FUN".into(),
             TokenType::For => "FOR".into(),
             TokenType::Nil => "NIL".into(),
-            TokenType::Or => "|".into(),
+            TokenType::Or => "OR".into(),
             TokenType::Print => "PRINT".into(),
             TokenType::Return => "RETURN".into(),
             TokenType::Super => "SUPER".into(),
             TokenType::This => "THIS".into(),
+            TokenType::Throw => "THROW".into(),
+            TokenType::Try => "TRY".into(),
             TokenType::Var => "VAR".into(),
             TokenType::While => "WHILE".into(),
             TokenType::EOF => "EOF".into(),